@@ -1,30 +1,23 @@
 // pausing game loop does not work with egui_console
 // press the GRAVE key to open the console
 
+use bevy::ecs::event::Events;
 use bevy::prelude::*;
-use bevy::{
-    ecs::{archetype::Archetypes, component::Components, entity::Entities},
-    reflect::TypeRegistry,
-};
 use bevy_console::{
     ConsoleCommandEntered, ConsoleConfiguration, ConsolePlugin, FromValue, PrintConsoleLine,
 };
-use bevy_mod_debug_console::{build_commands, match_commands, Pause};
+use bevy_mod_debug_console::{build_commands, expand_variables, match_commands, Pause};
 
 #[derive(Component)]
 struct Thing(String);
 
-fn debug_console(
-    mut console_events: EventReader<ConsoleCommandEntered>,
-    mut console_line: EventWriter<PrintConsoleLine>,
-    a: &Archetypes,
-    c: &Components,
-    e: &Entities,
-    mut pause: ResMut<Pause>,
-    reflect: Res<TypeRegistry>,
-) {
+fn debug_console(world: &mut World) {
     let app_name = "";
-    for event in console_events.iter() {
+    let console_events: Vec<ConsoleCommandEntered> = world
+        .resource_mut::<Events<ConsoleCommandEntered>>()
+        .drain()
+        .collect();
+    for event in console_events {
         let console_app = build_commands(app_name);
         let mut args = vec![app_name];
         args.push(&event.command);
@@ -36,16 +29,17 @@ fn debug_console(
             .collect();
         args.append(&mut split.iter().map(|s| s.as_ref()).collect());
 
-        let matches_result = console_app.try_get_matches_from(args);
-
-        if let Err(e) = matches_result {
-            console_line.send(PrintConsoleLine::new(e.to_string()));
-            return;
-        }
-
-        let output = match_commands(&matches_result.unwrap(), a, c, e, &mut pause, &*reflect);
+        let output = match expand_variables(world, &args) {
+            Ok(args) => match console_app.try_get_matches_from(args) {
+                Ok(matches) => match_commands(&matches, world),
+                Err(e) => e.to_string(),
+            },
+            Err(e) => e,
+        };
 
-        console_line.send(PrintConsoleLine::new(output));
+        world
+            .resource_mut::<Events<PrintConsoleLine>>()
+            .send(PrintConsoleLine::new(output));
     }
 }
 
@@ -65,6 +59,6 @@ fn main() {
         .add_plugin(ConsolePlugin)
         .insert_resource(Pause(false))
         .add_startup_system(setup)
-        .add_system(debug_console)
+        .add_system(debug_console.exclusive_system())
         .run();
 }
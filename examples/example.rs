@@ -4,6 +4,6 @@ use bevy_mod_debug_console::ConsoleDebugPlugin;
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugin(ConsoleDebugPlugin)
+        .add_plugin(ConsoleDebugPlugin::default())
         .run();
 }
\ No newline at end of file
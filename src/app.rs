@@ -1,82 +1,990 @@
+use crate::diagnostics;
 use crate::ecs;
+use crate::events;
+use crate::memory;
+use crate::network;
+#[cfg(feature = "console-puffin")]
+use crate::profile;
 use crate::reflect;
+#[cfg(feature = "console-render")]
+use crate::render;
+use crate::scripts;
+use crate::stress;
 use bevy::{
-    ecs::{archetype::Archetypes, component::Components, entity::Entities, schedule::ShouldRun},
-    prelude::{Input, KeyCode, Local, Res, ResMut},
+    ecs::schedule::ShouldRun,
+    app::AppExit,
+    prelude::{EventReader, EventWriter, Input, KeyCode, Local, Res, ResMut, Time, World},
     reflect::TypeRegistry,
 };
-use clap::{App, ArgMatches};
-use std::process::exit;
+use clap::{arg, App, AppSettings, Arg, ArgGroup, ArgMatches};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::exit,
+    str::FromStr,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
 pub fn build_commands(app_name: &str) -> App {
     let app = App::new(app_name);
 
     let app = build_app_commands(app);
+    let app = diagnostics::build_commands(app);
     let app = ecs::build_commands(app);
-    reflect::build_commands(app)
+    let app = events::build_commands(app);
+    let app = memory::build_commands(app);
+    let app = network::build_commands(app);
+    #[cfg(feature = "console-puffin")]
+    let app = profile::build_commands(app);
+    #[cfg(feature = "console-render")]
+    let app = render::build_commands(app);
+    let app = reflect::build_commands(app);
+    let app = scripts::build_commands(app);
+    stress::build_commands(app)
 }
 
-pub fn match_commands(
-    matches: &ArgMatches,
-    a: &Archetypes,
-    c: &Components,
-    e: &Entities,
-    pause: &mut Pause,
-    reflect: &TypeRegistry,
-) -> String {
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    // embedders without the plugin may not have the resource, that's an unlocked console
+    let locked = matches!(world.get_resource::<ConsoleLock>(), Some(ConsoleLock(Some(_))));
+    if locked && !is_unlock_command(matches) {
+        return String::from("Console locked. Use 'unlock --password <pwd>' to unlock.");
+    }
+
+    match_commands_unlocked(matches, world)
+}
+
+/// [`match_commands`] without the lock check, for sources in [`LockBypassSources`].
+pub(crate) fn match_commands_unlocked(matches: &ArgMatches, world: &mut World) -> String {
     let mut output = String::new();
 
-    output.push_str(&match_app_commands(matches, pause));
-    output.push_str(&ecs::match_commands(matches, a, c, e));
-    output.push_str(&reflect::match_commands(matches, reflect));
+    output.push_str(&match_app_commands(matches, world));
+    output.push_str(&ecs::match_commands(matches, world));
+    output.push_str(&diagnostics::match_commands(matches, world));
+    output.push_str(&events::match_commands(matches, world));
+    output.push_str(&memory::match_commands(matches, world));
+    output.push_str(&network::match_commands(matches, world));
+    output.push_str(&reflect::match_commands(
+        matches,
+        world.resource::<TypeRegistry>(),
+    ));
+    #[cfg(feature = "console-puffin")]
+    output.push_str(&profile::match_commands(matches, world));
+    #[cfg(feature = "console-render")]
+    output.push_str(&render::match_commands(matches, world));
+    output.push_str(&scripts::match_commands(matches, world));
+    output.push_str(&stress::match_commands(matches, world));
+
+    remember_entity(matches, world);
+
+    if let Ok(max_lines) = matches.value_of_t("max-lines") {
+        output = truncate_lines(&output, max_lines);
+    }
 
     output
 }
 
+/// Replaces `$last` (or `$last_entity`) arguments with the id of the entity the
+/// console last looked at, so commands can be chained.
+pub fn expand_variables(world: &World, args: &[&str]) -> Result<Vec<String>, String> {
+    args.iter()
+        .map(|arg| match *arg {
+            "$last" | "$last_entity" => world
+                .get_resource::<ConsoleVars>()
+                .and_then(|vars| vars.last_entity)
+                .map(|id| id.to_string())
+                .ok_or_else(|| {
+                    format!(
+                        "{} is not set yet, inspect an entity with 'entities info --id <Id>' first.",
+                        arg
+                    )
+                }),
+            _ => Ok(String::from(*arg)),
+        })
+        .collect()
+}
+
+/// Splits a line of input on `;` into the commands to run one after another. Semicolons inside
+/// `'` or `"` quotes, e.g. in `events fire --data`, don't split.
+pub fn split_commands(line: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, ch) in line.char_indices() {
+        match (ch, quote) {
+            ('\'' | '"', None) => quote = Some(ch),
+            (ch, Some(open)) if ch == open => quote = None,
+            (';', None) => {
+                commands.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    commands.push(&line[start..]);
+
+    commands
+        .into_iter()
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+fn remember_entity(matches: &ArgMatches, world: &mut World) {
+    let entity_id = match matches.subcommand() {
+        Some(("entities", matches)) => match matches.subcommand() {
+            Some(("info", matches)) => matches.value_of_t::<u32>("id").ok(),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    // freed ids still resolve, only remember ones with a live entity
+    let alive = |id: &u32| {
+        let entity = world.entities().resolve_from_id(*id);
+        entity.and_then(|entity| world.get_entity(entity)).is_some()
+    };
+    if let Some(id) = entity_id.filter(alive) {
+        world.get_resource_or_insert_with(ConsoleVars::default).last_entity = Some(id);
+    }
+}
+
+fn truncate_lines(output: &str, max_lines: usize) -> String {
+    let line_count = output.lines().count();
+    if line_count <= max_lines {
+        return String::from(output);
+    }
+
+    let mut truncated = String::new();
+    output.lines().take(max_lines).for_each(|line| {
+        truncated.push_str(line);
+        truncated.push('\n');
+    });
+    truncated.push_str(&format!(
+        "... (truncated, {} more lines)\n",
+        line_count - max_lines
+    ));
+
+    truncated
+}
+
+fn is_unlock_command(matches: &ArgMatches) -> bool {
+    match matches.subcommand() {
+        Some(("unlock", _)) => true,
+        Some(("console", matches)) => matches!(matches.subcommand(), Some(("unlock", _))),
+        _ => false,
+    }
+}
+
 fn build_app_commands(app: App) -> App {
     let app = app
+        .arg(
+            arg!(--"max-lines" <N> "truncate the output of any command to N lines")
+                .required(false)
+                .global(true),
+        )
+        .arg(
+            arg!(--"raw-name" "print full type paths instead of short names, overrides --long")
+                .global(true),
+        )
+        .arg(
+            arg!(--"path-depth" <N> "print the last N :: separated segments of type names, e.g. transform::Transform for 2")
+                .required(false)
+                .validator(|depth| match depth.parse::<usize>() {
+                    Ok(0) => Err(String::from("must be at least 1")),
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e.to_string()),
+                })
+                .global(true),
+        )
+        .arg(arg!(--bytes "print sizes as exact byte counts instead of KB/MB").global(true))
+        .arg(
+            arg!(--"bool-style" <Style> "print booleans as true/false or yes/no")
+                .required(false)
+                .possible_values(["true-false", "yes-no"])
+                .default_value("true-false")
+                .global(true),
+        )
+        .arg(
+            arg!(--"component-id-base" <Base> "print component ids as their index, 7, or raw, ComponentId(7)")
+                .required(false)
+                .possible_values(["index", "raw"])
+                .default_value("index")
+                .global(true),
+        )
+        .arg(
+            arg!(--"header-style" <Style> "print list headers as [component id], # component_id or component_id")
+                .required(false)
+                .possible_values(["brackets", "hash", "none"])
+                .default_value("brackets")
+                .global(true),
+        )
+        .arg(arg!(--pretty "pretty print JSON output instead of keeping it on one line").global(true))
+        .arg(
+            arg!(--indent <N> "pretty print JSON output indented by N spaces, implies --pretty")
+                .required(false)
+                .global(true),
+        )
         .subcommand(App::new("resume").about("resume running game"))
         .subcommand(App::new("pause").about("pause game tick"))
-        .subcommand(App::new("quit").about("quit game"));
+        .subcommand(App::new("quit").about("quit game"))
+        .subcommand(App::new("schema").about("print the command tree as JSON"))
+        .subcommand(
+            App::new("unlock")
+                .about("unlock a locked console, same as console unlock")
+                .arg(arg!(--password <Password> "password to unlock with")),
+        )
+        .subcommand(App::new("frame").about("print the frame count, elapsed and delta time"))
+        .subcommand(
+            App::new("bevy")
+                .about("information about the build")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("version")
+                        .about("print the bevy and console versions and enabled console features"),
+                ),
+        )
+        .subcommand(
+            App::new("console")
+                .about("configure the console")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("lock")
+                        .about("lock the console until it is unlocked with a password")
+                        .arg(arg!(--password <Hash> "bcrypt hash of the unlock password")),
+                )
+                .subcommand(
+                    App::new("unlock")
+                        .about("unlock a locked console")
+                        .arg(arg!(--password <Password> "password to unlock with")),
+                )
+                .subcommand(
+                    App::new("autoexec")
+                        .about("manage the commands run every time the console is opened")
+                        .args([
+                            arg!(--"on-open" <Command> "add a command to run when the console opens")
+                                .required(false)
+                                .multiple_values(true)
+                                .allow_hyphen_values(true),
+                            arg!(--list "list the commands run when the console opens"),
+                            arg!(--clear "remove all commands run when the console opens"),
+                        ])
+                        .group(ArgGroup::new("action")
+                            .args(&["on-open", "list", "clear"])
+                            .required(true)),
+                )
+                .subcommand(
+                    App::new("export")
+                        .about("save the commands run in this session and their output to a file")
+                        .args([
+                            arg!(--path <Path> "file to write, e.g. session.md"),
+                            arg!(--format <Format> "markdown with code blocks, or plain text")
+                                .required(false)
+                                .possible_values(["markdown", "plain"])
+                                .default_value("markdown"),
+                        ]),
+                )
+                .subcommand(
+                    App::new("run-on-startup")
+                        .about("add a command to the startup file, it runs from the next start on")
+                        .setting(AppSettings::TrailingVarArg)
+                        .arg(arg!(<command> ... "command to run").allow_hyphen_values(true)),
+                )
+                .subcommand(
+                    App::new("bind-key")
+                        .about("run a command when a key is pressed in the game window, 'list' lists the bindings")
+                        .setting(AppSettings::TrailingVarArg)
+                        .arg(arg!(<KeyCode> "key like F5, A or Key1, or list"))
+                        .arg(arg!([command] ... "command to run").allow_hyphen_values(true)),
+                )
+                .subcommand(
+                    App::new("unbind-key")
+                        .about("remove the command bound to a key")
+                        .arg(arg!(<KeyCode> "key like F5, A or Key1")),
+                )
+                .subcommand(
+                    App::new("history")
+                        .about("manage the commands run in the console")
+                        .setting(AppSettings::SubcommandRequiredElseHelp)
+                        .subcommand(App::new("clear").about("forget every command in the history"))
+                        .subcommand(
+                            App::new("save").about("write the history to the history file now"),
+                        )
+                        .subcommand(
+                            App::new("load")
+                                .about("replace the history with the commands in the history file"),
+                        )
+                        .subcommand(
+                            App::new("search")
+                                .about("print the commands containing Pattern with their index")
+                                .args([
+                                    arg!(<Pattern> "text to look for"),
+                                    arg!(--regex "match Pattern as a regular expression"),
+                                ]),
+                        ),
+                ),
+        );
 
     app
 }
 
-fn match_app_commands(matches: &ArgMatches, mut pause: &mut Pause) -> String {
+fn match_app_commands(matches: &ArgMatches, world: &mut World) -> String {
     let mut output = String::new();
     match matches.subcommand() {
         Some(("resume", _)) => {
-            pause.0 = false;
+            world.resource_mut::<Pause>().0 = false;
             output.push_str("...resuming game.");
         }
         Some(("pause", _)) => {
-            pause.0 = true;
+            world.resource_mut::<Pause>().0 = true;
             output.push_str("pausing game...");
         }
-        Some(("quit", _)) => exit(0),
+        Some(("quit", _)) => {
+            if let Some(Err(e)) = world
+                .get_resource::<ConsoleHistory>()
+                .filter(|history| history.file.is_some())
+                .map(ConsoleHistory::save)
+            {
+                println!("{}", e);
+            }
+            exit(0)
+        }
+        Some(("schema", _)) => output.push_str(&print_schema(ecs::json_indent(matches))),
+        Some(("unlock", matches)) => output.push_str(&unlock_command(matches, world)),
+        Some(("frame", _)) => output.push_str(&print_frame(world)),
+        Some(("bevy", matches)) => match matches.subcommand() {
+            Some(("version", _)) => output.push_str(&print_versions()),
+            _ => output.push_str("this line should not be hittable"),
+        },
+        Some(("console", matches)) => match matches.subcommand() {
+            Some(("lock", matches)) => {
+                let hash = matches.value_of("password").unwrap_or_default();
+                let mut lock = world.get_resource_or_insert_with(ConsoleLock::default);
+                output.push_str(&lock_console(&mut lock, hash));
+            }
+            Some(("unlock", matches)) => output.push_str(&unlock_command(matches, world)),
+            Some(("autoexec", matches)) => {
+                let on_open = &mut world.get_resource_or_insert_with(OnOpenCommands::default).0;
+                output.push_str(&autoexec(on_open, matches));
+            }
+            Some(("export", matches)) => {
+                let path = matches.value_of("path").unwrap_or_default();
+                let markdown = matches.value_of("format") == Some("markdown");
+                let session = world.get_resource_or_insert_with(ConsoleSession::default);
+                output.push_str(&export_session(&session, Path::new(path), markdown));
+            }
+            Some(("bind-key", matches)) => {
+                let key = matches.value_of("KeyCode").unwrap_or_default();
+                let command: Vec<&str> = matches.values_of("command").unwrap_or_default().collect();
+                let bindings = &mut world.get_resource_or_insert_with(KeyBindings::default).0;
+                output.push_str(&match (key, command.is_empty()) {
+                    ("list", true) => {
+                        list_key_bindings(bindings, ecs::HeaderStyle::from_matches(matches))
+                    }
+                    (_, true) => String::from("give a command to bind, or 'list' for the bindings."),
+                    (key, false) => bind_key(bindings, key, &command.join(" ")),
+                });
+            }
+            Some(("unbind-key", matches)) => {
+                let key = matches.value_of("KeyCode").unwrap_or_default();
+                let bindings = &mut world.get_resource_or_insert_with(KeyBindings::default).0;
+                output.push_str(&unbind_key(bindings, key));
+            }
+            Some(("history", matches)) => {
+                let mut history = world.get_resource_or_insert_with(ConsoleHistory::default);
+                // save and load only succeed with a file set
+                let path = history.file.clone().unwrap_or_default();
+                output.push_str(&match matches.subcommand() {
+                    Some(("clear", _)) => {
+                        let count = history.commands.len();
+                        history.commands.clear();
+                        format!("cleared {} commands from the history.", count)
+                    }
+                    Some(("save", _)) => match history.save() {
+                        Ok(count) => format!("saved {} commands to {}.", count, path.display()),
+                        Err(e) => e,
+                    },
+                    Some(("load", _)) => match history.load() {
+                        Ok(count) => format!("loaded {} commands from {}.", count, path.display()),
+                        Err(e) => e,
+                    },
+                    Some(("search", matches)) => search_history(
+                        &history,
+                        matches.value_of("Pattern").unwrap_or_default(),
+                        matches.is_present("regex"),
+                        ecs::HeaderStyle::from_matches(matches),
+                    ),
+                    _ => String::from("this line should not be hittable"),
+                });
+            }
+            Some(("run-on-startup", matches)) => {
+                let command: Vec<&str> = matches.values_of("command").unwrap_or_default().collect();
+                let command = command.join(" ");
+                let file = world
+                    .get_resource::<StartupCommands>()
+                    .and_then(|startup| startup.file.clone());
+                output.push_str(&match file {
+                    Some(path) => add_startup_command(&path, &command),
+                    None => String::from(
+                        "no startup file set, use ConsoleDebugPlugin::with_startup_file to set one.",
+                    ),
+                });
+            }
+            _ => output.push_str("this line should not be hittable"),
+        },
         _ => {}
     }
 
     output
 }
 
+fn autoexec(on_open: &mut Vec<String>, matches: &ArgMatches) -> String {
+    if let Some(command) = matches.values_of("on-open") {
+        // the console splits input on whitespace, put the command back together
+        let command = command.collect::<Vec<&str>>().join(" ");
+        let command = command.trim_matches(|c| c == '"' || c == '\'');
+        on_open.push(String::from(command));
+        format!("'{}' now runs when the console opens.", command)
+    } else if matches.is_present("clear") {
+        on_open.clear();
+        String::from("cleared the commands run when the console opens.")
+    } else if on_open.is_empty() {
+        String::from("no commands run when the console opens.")
+    } else {
+        on_open.join("\n")
+    }
+}
+
+fn export_session(session: &ConsoleSession, path: &Path, markdown: bool) -> String {
+    let mut text = String::new();
+    let exported = fmt_utc(SystemTime::now());
+    if markdown {
+        writeln!(text, "# Console session\n\nexported {}", exported).unwrap();
+        session.0.iter().for_each(|(command, output, time)| {
+            writeln!(text, "\n**`{}`** ({})\n", command, fmt_utc(*time)).unwrap();
+            writeln!(text, "```\n{}\n```", output.trim_end()).unwrap();
+        });
+    } else {
+        writeln!(text, "console session exported {}", exported).unwrap();
+        session.0.iter().for_each(|(command, output, time)| {
+            let time = fmt_utc(*time);
+            writeln!(text, "\n>>> {} ({})\n{}", command, time, output.trim_end()).unwrap();
+        });
+    }
+
+    match fs::write(path, text) {
+        Ok(()) => format!("exported {} commands to {}.", session.0.len(), path.display()),
+        Err(e) => format!("could not write to {}: {}", path.display(), e),
+    }
+}
+
+/// `2022-08-01 12:30:00 UTC`, formatted by hand to not pull in a date crate.
+fn fmt_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // civil_from_days from http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// The keys `console bind-key` takes, by the name of the [`KeyCode`] variant.
+fn parse_key_code(name: &str) -> Option<KeyCode> {
+    use KeyCode::*;
+    const KEYS: [KeyCode; 54] = [
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, Key0, Key1, Key2, Key3, Key4, Key5,
+        Key6, Key7, Key8, Key9, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V,
+        W, X, Y, Z, Home, End, Insert, Delete, PageUp, PageDown,
+    ];
+    KEYS.into_iter()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))
+}
+
+fn bind_key(bindings: &mut HashMap<KeyCode, String>, key: &str, command: &str) -> String {
+    let key = match parse_key_code(key) {
+        Some(key) => key,
+        None => {
+            return format!(
+                "unknown key {}, use F1 to F12, A to Z, Key0 to Key9, Home, End, Insert, Delete, PageUp or PageDown.",
+                key
+            )
+        }
+    };
+    // F10 pauses the game for the console, a command on it would run when the game resumes
+    let note = if key == KeyCode::F10 { "\nnote: F10 also pauses the game." } else { "" };
+    match bindings.insert(key, String::from(command)) {
+        Some(old) => format!("{:?} now runs '{}' instead of '{}'.{}", key, command, old, note),
+        None => format!("{:?} now runs '{}'.{}", key, command, note),
+    }
+}
+
+fn unbind_key(bindings: &mut HashMap<KeyCode, String>, key: &str) -> String {
+    let key = match parse_key_code(key) {
+        Some(key) => key,
+        None => return format!("unknown key {}, nothing is bound to it.", key),
+    };
+    match bindings.remove(&key) {
+        Some(command) => format!("{:?} no longer runs '{}'.", key, command),
+        None => format!("nothing is bound to {:?}.", key),
+    }
+}
+
+fn list_key_bindings(
+    bindings: &HashMap<KeyCode, String>,
+    header_style: ecs::HeaderStyle,
+) -> String {
+    if bindings.is_empty() {
+        return String::from("no keys bound, add one with 'console bind-key <KeyCode> <command>'.");
+    }
+
+    let mut rows: Vec<(&KeyCode, &String)> = bindings.iter().collect();
+    rows.sort();
+    let mut output = String::new();
+    ecs::write_header(&mut output, header_style, &["key", "command"]);
+    rows.iter()
+        .for_each(|(key, command)| writeln!(output, "{:?} {}", key, command).unwrap());
+
+    output
+}
+
+/// `[index] [command]` for every command in the history matching `pattern`, newest last.
+fn search_history(
+    history: &ConsoleHistory,
+    pattern: &str,
+    regex: bool,
+    header_style: ecs::HeaderStyle,
+) -> String {
+    let regex = match regex.then(|| Regex::new(pattern)).transpose() {
+        Ok(regex) => regex,
+        Err(e) => return format!("invalid --regex pattern: {}", e),
+    };
+    let found: Vec<(usize, &String)> = history
+        .commands
+        .iter()
+        .enumerate()
+        .filter(|(_, command)| match &regex {
+            Some(regex) => regex.is_match(command),
+            None => command.contains(pattern),
+        })
+        .collect();
+
+    if found.is_empty() {
+        return format!("no commands in the history match {}.", pattern);
+    }
+    let mut output = String::new();
+    ecs::write_header(&mut output, header_style, &["index", "command"]);
+    found
+        .iter()
+        .for_each(|(index, command)| writeln!(output, "{} {}", index + 1, command).unwrap());
+
+    output
+}
+
+fn add_startup_command(path: &Path, command: &str) -> String {
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", command));
+
+    match result {
+        Ok(()) => format!("added '{}' to {}.", command, path.display()),
+        Err(e) => format!("could not write to {}: {}", path.display(), e),
+    }
+}
+
+/// The bevy version the crate is built against. bevy has no version constant to read, so this
+/// follows the requirement in Cargo.toml, cargo picks a patch release of it.
+const BEVY_VERSION: &str = "0.8";
+
+/// `bevy 0.8, bevy_mod_debug_console 0.1.0`
+pub(crate) fn versions() -> String {
+    format!(
+        "bevy {}, bevy_mod_debug_console {}",
+        BEVY_VERSION,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+fn print_versions() -> String {
+    let features: Vec<&str> = [
+        ("console-dhat", cfg!(feature = "console-dhat")),
+        ("console-puffin", cfg!(feature = "console-puffin")),
+        ("console-render", cfg!(feature = "console-render")),
+        ("console-scene", cfg!(feature = "console-scene")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(feature, _)| feature)
+    .collect();
+
+    let mut output = String::new();
+    writeln!(output, "bevy: {}", BEVY_VERSION).unwrap();
+    writeln!(output, "bevy_mod_debug_console: {}", env!("CARGO_PKG_VERSION")).unwrap();
+    // the console is locked in release builds, so it is worth knowing which one this is
+    let build = if cfg!(debug_assertions) { "debug" } else { "release" };
+    writeln!(output, "build: {}", build).unwrap();
+    if features.is_empty() {
+        writeln!(output, "features: none").unwrap();
+    } else {
+        writeln!(output, "features: {}", features.join(", ")).unwrap();
+    }
+
+    output
+}
+
+fn print_frame(world: &World) -> String {
+    let mut output = String::new();
+    if let Some(frames) = world.get_resource::<FrameCount>() {
+        writeln!(output, "frame: {}", frames.0).unwrap();
+    }
+    match world.get_resource::<Time>() {
+        Some(time) => {
+            writeln!(output, "elapsed: {:.3} s", time.seconds_since_startup()).unwrap();
+            writeln!(output, "delta: {:.2} ms", time.delta_seconds_f64() * 1000.0).unwrap();
+        }
+        None => output.push_str("no Time resource found.\n"),
+    }
+
+    output
+}
+
+fn print_schema(indent: Option<usize>) -> String {
+    format!("{}\n", ecs::fmt_json(&command_schema(&build_commands("")), indent))
+}
+
+fn command_schema(app: &App) -> Value {
+    json!({
+        "name": app.get_name(),
+        "about": app.get_about(),
+        "args": app
+            .get_arguments()
+            // clap's own flags aren't useful to a front-end
+            .filter(|arg| !matches!(arg.get_id(), "help" | "version"))
+            .map(arg_schema)
+            .collect::<Vec<Value>>(),
+        "subcommands": app.get_subcommands().map(command_schema).collect::<Vec<Value>>(),
+    })
+}
+
+fn arg_schema(arg: &Arg) -> Value {
+    json!({
+        "name": arg.get_id(),
+        "long": arg.get_long(),
+        "short": arg.get_short(),
+        "help": arg.get_help(),
+        "required": arg.is_required_set(),
+        "takes_value": arg.is_takes_value_set(),
+        "value_names": arg.get_value_names(),
+        "global": arg.is_global_set(),
+    })
+}
+
+fn lock_console(lock: &mut ConsoleLock, hash: &str) -> String {
+    // refuse hashes we could never verify, otherwise the console can't be unlocked again
+    if bcrypt::HashParts::from_str(hash).is_err() {
+        return String::from("--password must be a valid bcrypt hash, console not locked.");
+    }
+
+    lock.0 = Some(String::from(hash));
+    String::from("console locked.")
+}
+
+fn unlock_command(matches: &ArgMatches, world: &mut World) -> String {
+    let password = matches.value_of("password").unwrap_or_default();
+    match world.get_resource_mut::<ConsoleLock>() {
+        Some(mut lock) => unlock_console(&mut lock, password),
+        None => String::from("console is not locked."),
+    }
+}
+
+fn unlock_console(lock: &mut ConsoleLock, password: &str) -> String {
+    match &lock.0 {
+        Some(hash) => {
+            if let Ok(true) = bcrypt::verify(password, hash) {
+                lock.0 = None;
+                String::from("console unlocked.")
+            } else {
+                String::from("incorrect password.")
+            }
+        }
+        None => String::from("console is not locked."),
+    }
+}
+
 #[derive(Default)]
 pub struct Pause(pub bool);
 pub struct EnteringConsole(pub bool);
+/// Holds the bcrypt hash the console is locked with, `None` when unlocked.
+#[derive(Default)]
+pub struct ConsoleLock(pub Option<String>);
+/// Where a [`ConsoleInputEvent`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandSource {
+    Keyboard,
+    Script,
+    Remote,
+    Programmatic,
+}
+
+impl std::fmt::Display for CommandSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            CommandSource::Keyboard => "keyboard",
+            CommandSource::Script => "script",
+            CommandSource::Remote => "remote",
+            CommandSource::Programmatic => "programmatic",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Send this event to run a console command from game code, e.g. a remote debugging RPC.
+/// The output is printed after the source and the command.
+pub struct ConsoleInputEvent {
+    pub command: String,
+    pub source: CommandSource,
+}
+
+/// Sources whose [`ConsoleInputEvent`]s run even while the console is locked, see
+/// [`ConsoleDebugPlugin::with_lock_bypass`].
+///
+/// [`ConsoleDebugPlugin::with_lock_bypass`]: crate::ConsoleDebugPlugin::with_lock_bypass
+#[derive(Default)]
+pub struct LockBypassSources(pub Vec<CommandSource>);
+/// Every command run in the console with its output and when it ran, for `console export`.
+#[derive(Default)]
+pub struct ConsoleSession(pub Vec<(String, String, SystemTime)>);
+
+/// How many commands [`ConsoleHistory`] remembers, the oldest are dropped first.
+const HISTORY_KEPT: usize = 1000;
+
+/// The last commands run in the console, oldest first, see `console history` and
+/// [`ConsoleDebugPlugin::with_history_file`].
+///
+/// [`ConsoleDebugPlugin::with_history_file`]: crate::ConsoleDebugPlugin::with_history_file
+#[derive(Default)]
+pub struct ConsoleHistory {
+    pub commands: VecDeque<String>,
+    pub file: Option<PathBuf>,
+}
+
+impl ConsoleHistory {
+    /// Loads `file` right away, a missing file just has no history yet.
+    pub fn new(file: Option<PathBuf>) -> Self {
+        let mut history = ConsoleHistory {
+            commands: VecDeque::new(),
+            file,
+        };
+        let _ = history.load();
+        history
+    }
+
+    pub fn push(&mut self, command: &str) {
+        if command.trim().is_empty() || self.commands.back().map(String::as_str) == Some(command) {
+            return;
+        }
+        if self.commands.len() == HISTORY_KEPT {
+            self.commands.pop_front();
+        }
+        self.commands.push_back(String::from(command));
+    }
+
+    /// Replaces the history with the commands in the history file.
+    pub fn load(&mut self) -> Result<usize, String> {
+        let path = self.file.as_ref().ok_or_else(no_history_file)?;
+        let file = fs::read_to_string(path)
+            .map_err(|e| format!("could not read history from {}: {}", path.display(), e))?;
+        self.commands.clear();
+        file.lines().for_each(|command| self.push(command));
+
+        Ok(self.commands.len())
+    }
+
+    /// Writes the history to the history file, one command per line.
+    pub fn save(&self) -> Result<usize, String> {
+        let path = self.file.as_ref().ok_or_else(no_history_file)?;
+        let mut text = String::new();
+        self.commands
+            .iter()
+            .for_each(|command| writeln!(text, "{}", command).unwrap());
+        fs::write(path, text)
+            .map_err(|e| format!("could not write history to {}: {}", path.display(), e))?;
+
+        Ok(self.commands.len())
+    }
+}
+
+fn no_history_file() -> String {
+    String::from("no history file set, use ConsoleDebugPlugin::with_history_file to set one.")
+}
+
+/// Saves the [`ConsoleHistory`] when the app exits.
+pub fn save_history_on_exit(mut exit: EventReader<AppExit>, history: Option<Res<ConsoleHistory>>) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    if let Some(Err(e)) = history.filter(|history| history.file.is_some()).map(|h| h.save()) {
+        println!("{}", e);
+    }
+}
+/// Values the console substitutes for `$` variables, see [`expand_variables`].
+#[derive(Default)]
+pub struct ConsoleVars {
+    pub last_entity: Option<u32>,
+}
+/// Commands run once at startup, see [`ConsoleDebugPlugin::with_startup_commands`].
+///
+/// [`ConsoleDebugPlugin::with_startup_commands`]: crate::ConsoleDebugPlugin::with_startup_commands
+#[derive(Default)]
+pub struct StartupCommands {
+    pub commands: Vec<String>,
+    pub file: Option<PathBuf>,
+}
+/// Commands run every time the console is opened, see [`ConsoleDebugPlugin::with_on_open_commands`].
+///
+/// [`ConsoleDebugPlugin::with_on_open_commands`]: crate::ConsoleDebugPlugin::with_on_open_commands
+#[derive(Default)]
+pub struct OnOpenCommands(pub Vec<String>);
+/// Put in front of every line the console prints, see [`ConsoleDebugPlugin::with_response_prefix`].
+///
+/// [`ConsoleDebugPlugin::with_response_prefix`]: crate::ConsoleDebugPlugin::with_response_prefix
+#[derive(Default)]
+pub struct ResponsePrefix(pub String);
+
+impl ResponsePrefix {
+    pub fn apply(&self, output: &str) -> String {
+        if self.0.is_empty() {
+            return String::from(output);
+        }
+        output
+            .lines()
+            .map(|line| format!("{}{}\n", self.0, line))
+            .collect()
+    }
+}
+/// Commands run when their key is pressed, see `console bind-key` and
+/// [`ConsoleDebugPlugin::with_default_key_bindings`].
+///
+/// [`ConsoleDebugPlugin::with_default_key_bindings`]: crate::ConsoleDebugPlugin::with_default_key_bindings
+#[derive(Default)]
+pub struct KeyBindings(pub HashMap<KeyCode, String>);
+
+/// Sends a [`ConsoleInputEvent`] for every bound key pressed this frame.
+pub fn run_key_bindings(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut events: EventWriter<ConsoleInputEvent>,
+) {
+    bindings
+        .0
+        .iter()
+        .filter(|(key, _)| keyboard_input.just_pressed(**key))
+        .for_each(|(_, command)| {
+            events.send(ConsoleInputEvent {
+                command: command.clone(),
+                source: CommandSource::Keyboard,
+            })
+        });
+}
+/// Number of frames run since the app started, counted by [`count_frames`].
+#[derive(Default)]
+pub struct FrameCount(pub u64);
+
+pub fn count_frames(mut frames: ResMut<FrameCount>) {
+    frames.0 += 1;
+}
+
+/// How many frames [`TickToTimeMap`] remembers, a minute at 60 fps.
+const TICK_TIMES_KEPT: usize = 3600;
+
+/// The change tick and wall-clock time at the start of each recent frame, so ticks can be shown
+/// as rough times. Only recorded in development builds.
+#[derive(Default)]
+pub struct TickToTimeMap(pub VecDeque<(u32, Instant)>);
+
+pub fn record_tick_time(world: &mut World) {
+    let tick = world.read_change_tick();
+    let mut times = world.get_resource_or_insert_with(TickToTimeMap::default);
+    if times.0.len() == TICK_TIMES_KEPT {
+        times.0.pop_front();
+    }
+    times.0.push_back((tick, Instant::now()));
+}
+
 pub fn pause(
     pause: Res<Pause>,
+    pending_list: Option<Res<ecs::PendingListOperation>>,
     mut last_pause: Local<Pause>,
     mut entering_console: ResMut<EnteringConsole>,
 ) -> ShouldRun {
     entering_console.0 = (pause.0 != last_pause.0) && pause.0;
     last_pause.0 = pause.0;
-    if pause.0 {
+    // a chunked `entities list` loads one chunk a frame, so the frame has to end in between
+    if pause.0 && pending_list.is_none() {
         ShouldRun::YesAndCheckAgain
     } else {
         ShouldRun::Yes
     }
 }
 
+/// Set by `world freeze`, stops [`CoreStage::Update`](bevy::prelude::CoreStage::Update) and the
+/// systems run with the [`not_frozen`] run criteria while the rest of the app, the console
+/// included, keeps running.
+#[derive(Default)]
+pub struct WorldFrozen(pub bool);
+
+/// Frames `world step` lets run while the world is frozen.
+#[derive(Default)]
+pub struct StepMode {
+    pub steps_remaining: u32,
+    /// whether this frame is one of the steps, set at the start of each frame
+    pub stepping: bool,
+}
+
+/// Takes one of the remaining steps at the start of a frame, so a step is always a whole frame.
+pub fn take_step(mut step: ResMut<StepMode>) {
+    step.stepping = step.steps_remaining > 0;
+    step.steps_remaining = step.steps_remaining.saturating_sub(1);
+}
+
+/// Run criteria for game systems that should stop while the world is frozen. `ConsoleDebugPlugin`
+/// sets it on `CoreStage::Update`, systems in other stages need it themselves, i.e.
+/// `.add_system_to_stage(CoreStage::PostUpdate, movement.with_run_criteria(not_frozen))`.
+pub fn not_frozen(frozen: Option<Res<WorldFrozen>>, step: Option<Res<StepMode>>) -> ShouldRun {
+    let frozen = matches!(frozen, Some(frozen) if frozen.0);
+    let stepping = matches!(step, Some(step) if step.stepping);
+    if frozen && !stepping {
+        ShouldRun::No
+    } else {
+        ShouldRun::Yes
+    }
+}
+
 pub fn input_pause(keyboard_input: Res<Input<KeyCode>>, mut pause: ResMut<Pause>) {
     if keyboard_input.pressed(KeyCode::F10) {
         pause.0 = true;
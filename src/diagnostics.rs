@@ -0,0 +1,251 @@
+use std::fmt::Write;
+
+use bevy::{
+    diagnostic::{Diagnostic, Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::{Local, Time, World},
+};
+use clap::{arg, App, AppSettings, ArgMatches};
+
+use crate::ecs::{write_header, HeaderStyle};
+
+/// Whether the fps line is printed to the console every second.
+#[derive(Default)]
+pub struct FpsHudVisible(pub bool);
+/// Number of frame times drawn as a sparkline next to the fps, 0 to hide it.
+#[derive(Default)]
+pub struct FpsHudHistory(pub usize);
+
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    values
+        .iter()
+        .map(|value| {
+            if range > 0.0 {
+                let bar = (value - min) / range * (SPARKLINE_BARS.len() - 1) as f64;
+                SPARKLINE_BARS[bar.round() as usize]
+            } else {
+                SPARKLINE_BARS[0]
+            }
+        })
+        .collect()
+}
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("fps")
+            .about("show frame rate info, requires FrameTimeDiagnosticsPlugin")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                App::new("show")
+                    .about("print the fps every second")
+                    .arg(arg!(--history [N] "also draw the last N frame times as a sparkline")),
+            )
+            .subcommand(App::new("hide").about("stop printing the fps"))
+            .subcommand(App::new("stats").about("print min, max and mean frame time")),
+    )
+    .subcommand(
+        App::new("diagnostics")
+            .about("get info from bevy's Diagnostics")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(App::new("list").about("list all registered diagnostics"))
+            .subcommand(
+                App::new("print")
+                    .about("print the recent measurements of one diagnostic")
+                    .args([
+                        arg!(--name <Name> "name of the diagnostic"),
+                        arg!(--count [N] "number of measurements to print, defaults to all kept"),
+                        arg!(--format [Format] "how to print the measurements")
+                            .possible_values(["list", "sparkline"])
+                            .default_value("list"),
+                    ]),
+            ),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("fps", matches)) => match matches.subcommand() {
+            Some(("show", matches)) => {
+                let has_fps = world
+                    .get_resource::<Diagnostics>()
+                    .and_then(|diagnostics| diagnostics.get(FrameTimeDiagnosticsPlugin::FPS))
+                    .is_some();
+                if !has_fps {
+                    return missing_frame_time_diagnostics();
+                }
+                world.get_resource_or_insert_with(FpsHudVisible::default).0 = true;
+                world.get_resource_or_insert_with(FpsHudHistory::default).0 =
+                    matches.value_of_t("history").unwrap_or(0);
+                String::from("showing fps.\n")
+            }
+            Some(("hide", _)) => {
+                world.get_resource_or_insert_with(FpsHudVisible::default).0 = false;
+                String::from("hiding fps.\n")
+            }
+            Some(("stats", _)) => print_frame_time_stats(world.get_resource::<Diagnostics>()),
+            _ => String::from("this line should not be hittable"),
+        },
+        Some(("diagnostics", matches)) => {
+            let diagnostics = match world.get_resource::<Diagnostics>() {
+                Some(diagnostics) => diagnostics,
+                None => {
+                    return String::from(
+                        "no Diagnostics resource found, add `DiagnosticsPlugin` to the app.\n",
+                    )
+                }
+            };
+            match matches.subcommand() {
+                Some(("list", matches)) => {
+                    list_diagnostics(diagnostics, HeaderStyle::from_matches(matches))
+                }
+                Some(("print", matches)) => {
+                    let name = matches.value_of("name").unwrap_or_default();
+                    let count = matches.value_of_t("count").ok();
+                    let sparkline = matches.value_of("format") == Some("sparkline");
+                    print_diagnostic(diagnostics, name, count, sparkline)
+                }
+                _ => String::from("this line should not be hittable"),
+            }
+        }
+        _ => String::from(""),
+    }
+}
+
+fn missing_frame_time_diagnostics() -> String {
+    String::from("no frame time diagnostics found, add `FrameTimeDiagnosticsPlugin` to the app.\n")
+}
+
+/// The last `count` frame times in milliseconds, oldest first.
+fn frame_times_ms(frame_time: &Diagnostic, count: usize) -> Vec<f64> {
+    let values: Vec<f64> = frame_time
+        .values()
+        .map(|seconds| seconds * 1000.0)
+        .collect();
+    values[values.len().saturating_sub(count)..].to_vec()
+}
+
+fn print_frame_time_stats(diagnostics: Option<&Diagnostics>) -> String {
+    let frame_time = match diagnostics.and_then(|d| d.get(FrameTimeDiagnosticsPlugin::FRAME_TIME)) {
+        Some(frame_time) => frame_time,
+        None => return missing_frame_time_diagnostics(),
+    };
+
+    let times = frame_times_ms(frame_time, frame_time.history_len());
+    if times.is_empty() {
+        return String::from("no frame times measured yet.\n");
+    }
+
+    let min = times.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = times.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+
+    let mut output = String::new();
+    writeln!(output, "frame time over the last {} frames:", times.len()).unwrap();
+    writeln!(output, "min: {:.2} ms", min).unwrap();
+    writeln!(output, "max: {:.2} ms", max).unwrap();
+    writeln!(output, "mean: {:.2} ms", mean).unwrap();
+
+    output
+}
+
+fn list_diagnostics(diagnostics: &Diagnostics, header_style: HeaderStyle) -> String {
+    let mut rows: Vec<(&str, String)> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let value = diagnostic
+                .value()
+                .map(|value| format!("{:.4}{}", value, diagnostic.suffix))
+                .unwrap_or_default();
+            (diagnostic.name.as_ref(), value)
+        })
+        .collect();
+    rows.sort();
+
+    let mut output = String::new();
+    write_header(&mut output, header_style, &["diagnostic name", "latest value"]);
+    rows.iter()
+        .for_each(|(name, value)| writeln!(output, "{} {}", name, value).unwrap());
+
+    output
+}
+
+fn print_diagnostic(
+    diagnostics: &Diagnostics,
+    name: &str,
+    count: Option<usize>,
+    sparkline_format: bool,
+) -> String {
+    let diagnostic = match diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.name == name)
+    {
+        Some(diagnostic) => diagnostic,
+        None => return format!("No diagnostic found with name: {}\n", name),
+    };
+
+    let values: Vec<f64> = diagnostic.values().cloned().collect();
+    let values = &values[values.len().saturating_sub(count.unwrap_or(values.len()))..];
+    if values.is_empty() {
+        return format!("{} has no measurements yet\n", name);
+    }
+
+    let mut output = String::new();
+    writeln!(output, "{} (last {} measurements)", name, values.len()).unwrap();
+    if sparkline_format {
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        writeln!(output, "{}", sparkline(values)).unwrap();
+        writeln!(output, "min: {:.4}{}", min, diagnostic.suffix).unwrap();
+        writeln!(output, "max: {:.4}{}", max, diagnostic.suffix).unwrap();
+    } else {
+        values
+            .iter()
+            .for_each(|value| writeln!(output, "{:.4}{}", value, diagnostic.suffix).unwrap());
+    }
+    let average = values.iter().sum::<f64>() / values.len() as f64;
+    writeln!(output, "average: {:.4}{}", average, diagnostic.suffix).unwrap();
+
+    output
+}
+
+/// Text for the fps line, `None` if it's hidden or there is nothing measured yet.
+pub fn fps_hud_text(world: &World) -> Option<String> {
+    if !world.get_resource::<FpsHudVisible>()?.0 {
+        return None;
+    }
+
+    let diagnostics = world.get_resource::<Diagnostics>()?;
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)?
+        .average()?;
+
+    let mut output = format!("fps: {:.1}", fps);
+    let history = world.get_resource::<FpsHudHistory>().map_or(0, |h| h.0);
+    if history > 0 {
+        if let Some(frame_time) = diagnostics.get(FrameTimeDiagnosticsPlugin::FRAME_TIME) {
+            output.push(' ');
+            output.push_str(&sparkline(&frame_times_ms(frame_time, history)));
+        }
+    }
+
+    Some(output)
+}
+
+pub fn print_fps_hud(world: &World, mut last_print: Local<f64>) {
+    let now = world.resource::<Time>().seconds_since_startup();
+    if now - *last_print < 1.0 {
+        return;
+    }
+    *last_print = now;
+
+    if let Some(text) = fps_hud_text(world) {
+        println!("{}", text);
+    }
+}
@@ -1,52 +1,395 @@
-use std::fmt::Write;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Write,
+    num::ParseIntError,
+    ops::{Range, RangeInclusive},
+    path::Path,
+    str::FromStr,
+    time::Instant,
+};
 
 use bevy::{
+    core::Name,
     ecs::{
-        archetype::{ArchetypeId, Archetypes},
-        component::{ComponentId, Components, StorageType},
+        archetype::{Archetype, ArchetypeId, Archetypes},
+        component::{ComponentId, ComponentInfo, ComponentTicks, Components, StorageType},
         entity::{Entities, Entity},
+        reflect::{ReflectComponent, ReflectMapEntities},
+        world::World,
     },
+    hierarchy::{Children, Parent},
+    reflect::{std_traits::ReflectDefault, ReflectDeserialize, TypeRegistry},
     utils::get_short_name,
 };
-use clap::{App, AppSettings, ArgGroup, ArgMatches, arg};
+use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, arg};
+
+use crate::app::{versions, FrameCount, StepMode, TickToTimeMap, WorldFrozen};
+use crate::inspect::inspect_world;
+use crate::memory::fmt_size;
+use crate::scene::{export_scene, import_scene};
+use crate::stress::{random_seed, shuffle_front};
+use crate::validate::validate_world;
+use serde::Serialize;
+use serde_json::json;
+
+/// Which slice of a long listing to print, from `--limit <N>` and `--page <N>`.
+#[derive(Clone, Copy, Default)]
+struct Pagination {
+    limit: Option<usize>,
+    page: usize,
+    /// pick this many random items with the seed before paging, see [`Pagination::with_sample`]
+    sample: Option<(usize, u64)>,
+}
+
+impl Pagination {
+    fn args<'help>() -> [Arg<'help>; 2] {
+        [
+            arg!(--limit <N> "only print N items").required(false),
+            arg!(--page <N> "print the Nth page of --limit items, starting at 1")
+                .required(false)
+                .requires("limit"),
+        ]
+    }
+
+    fn from_matches(matches: &ArgMatches) -> Self {
+        Pagination {
+            limit: matches.value_of_t("limit").ok(),
+            page: matches.value_of_t("page").unwrap_or(1).max(1),
+            sample: None,
+        }
+    }
+
+    /// Also reads `--sample` and `--seed`, for the commands that take them.
+    fn with_sample(mut self, matches: &ArgMatches) -> Self {
+        if let Ok(count) = matches.value_of_t("sample") {
+            let seed = matches.value_of_t("seed").unwrap_or_else(|_| random_seed());
+            self.sample = Some((count, seed));
+        }
+        self
+    }
+
+    /// The items on this page and a footer saying how to get the next one, empty when
+    /// everything was printed.
+    fn apply<'a, T>(&self, items: &'a [T]) -> (&'a [T], String) {
+        let limit = match self.limit {
+            Some(limit) => limit.max(1),
+            None => return (items, String::new()),
+        };
+
+        let start = (self.page - 1).saturating_mul(limit).min(items.len());
+        let end = start.saturating_add(limit).min(items.len());
+        let footer = if start == items.len() && !items.is_empty() {
+            format!(
+                "(page {} is past the end, there are {} pages)\n",
+                self.page,
+                (items.len() + limit - 1) / limit
+            )
+        } else if end < items.len() {
+            format!(
+                "(showing {}-{} of {}, run with --page {} for more)\n",
+                start + 1,
+                end,
+                items.len(),
+                self.page + 1
+            )
+        } else {
+            String::new()
+        };
+
+        (&items[start..end], footer)
+    }
+}
+
+/// `120 components`, or `12 of 120 components (filtered)` when some were left out
+fn count_footer(matched: usize, total: usize, noun: &str) -> String {
+    if matched == total {
+        format!("{} {}\n", total, noun)
+    } else {
+        format!("{} of {} {} (filtered)\n", matched, total, noun)
+    }
+}
+
+/// How listing headers like `[component id] [component name]` are printed, set with the global
+/// `--header-style`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HeaderStyle {
+    /// `[component id] [component name]`
+    Brackets,
+    /// `# component_id component_name`
+    Hash,
+    /// `component_id component_name`
+    Plain,
+}
+
+impl HeaderStyle {
+    pub(crate) fn from_matches(matches: &ArgMatches) -> Self {
+        match matches.value_of("header-style") {
+            Some("hash") => HeaderStyle::Hash,
+            Some("none") => HeaderStyle::Plain,
+            _ => HeaderStyle::Brackets,
+        }
+    }
+}
+
+/// Writes the header line of a listing, i.e. `[component id] [component name]` in the default
+/// `--header-style`.
+pub(crate) fn write_header(output: &mut String, style: HeaderStyle, columns: &[&str]) {
+    write_padded_header(output, style, columns, 0);
+}
+
+/// [`write_header`] for listings whose rows pad every column but the last to `width`, so the
+/// header lines up with them.
+pub(crate) fn write_padded_header(
+    output: &mut String,
+    style: HeaderStyle,
+    columns: &[&str],
+    width: usize,
+) {
+    let mut header = String::from(if style == HeaderStyle::Hash { "# " } else { "" });
+    for (i, name) in columns.iter().enumerate() {
+        if i > 0 {
+            header.push(' ');
+            while header.len() < width * i {
+                header.push(' ');
+            }
+        }
+        match style {
+            HeaderStyle::Brackets => write!(header, "[{}]", name).unwrap(),
+            // so the header splits on whitespace like its rows
+            HeaderStyle::Hash | HeaderStyle::Plain => header.push_str(&name.replace(' ', "_")),
+        }
+    }
+    writeln!(output, "{}", header).unwrap();
+}
+
+/// How type names are printed, picked per command from `--long`, `--raw-name` and
+/// `--path-depth`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NameStyle {
+    /// `Transform`
+    Short,
+    /// the last N path segments, i.e. `transform::Transform` for 2
+    Depth(usize),
+    /// `bevy_transform::components::transform::Transform`
+    Full,
+}
+
+impl NameStyle {
+    /// `--raw-name` and `--long` print full paths and win over `--path-depth`. Not every command
+    /// has `--long`.
+    fn from_matches(matches: &ArgMatches, raw_name: bool) -> Self {
+        if raw_name || matches!(matches.try_contains_id("long"), Ok(true)) {
+            return NameStyle::Full;
+        }
+        match matches.value_of_t("path-depth") {
+            Ok(depth) if depth > 0 => NameStyle::Depth(depth),
+            _ => NameStyle::Short,
+        }
+    }
+
+    /// For lookups that match against either the short names or the full paths.
+    fn matching(short: bool) -> Self {
+        if short {
+            NameStyle::Short
+        } else {
+            NameStyle::Full
+        }
+    }
+}
+
+/// get_short_name removes the path information
+/// i.e. `bevy_audio::audio::Audio` -> `Audio`
+pub fn type_name(name: &str, name_style: NameStyle) -> String {
+    match name_style {
+        NameStyle::Short => get_short_name(name),
+        NameStyle::Depth(depth) => trailing_path(name, depth),
+        NameStyle::Full => String::from(name),
+    }
+}
+
+/// Keeps the last `depth` segments of every path in `name`, including the ones in generics, i.e.
+/// `bevy_asset::handle::Handle<bevy_render::mesh::mesh::Mesh>` -> `handle::Handle<mesh::Mesh>`
+/// for 2.
+fn trailing_path(name: &str, depth: usize) -> String {
+    let mut output = String::new();
+    let mut rest = name;
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .unwrap_or(rest.len());
+        let (path, delimiters) = rest.split_at(end);
+        let segments: Vec<&str> = path.split("::").collect();
+        output.push_str(&segments[segments.len().saturating_sub(depth)..].join("::"));
+
+        let next = delimiters
+            .find(|c: char| c.is_alphanumeric() || c == '_')
+            .unwrap_or(delimiters.len());
+        output.push_str(&delimiters[..next]);
+        rest = &delimiters[next..];
+    }
+
+    output
+}
+
+/// Renders a bool the same way everywhere, `yes`/`no` when `--bool-style yes-no` was passed.
+pub fn fmt_bool(value: bool, yes_no: bool) -> &'static str {
+    match (value, yes_no) {
+        (true, false) => "true",
+        (false, false) => "false",
+        (true, true) => "yes",
+        (false, true) => "no",
+    }
+}
+
+/// Prints a component id as the number it is indexed by, or as the raw `ComponentId(7)` with
+/// `--component-id-base raw`. Commands taking a component id accept both.
+pub fn fmt_component_id(id: usize, raw: bool) -> String {
+    if raw {
+        format!("{:?}", ComponentId::new(id))
+    } else {
+        id.to_string()
+    }
+}
+
+/// A component id given on the command line, either `7` or `ComponentId(7)` as printed with
+/// `--component-id-base raw`.
+struct ComponentIdArg(usize);
+
+impl FromStr for ComponentIdArg {
+    type Err = ParseIntError;
 
-pub fn list_resources(archetypes: &Archetypes, components: &Components) -> String {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s
+            .strip_prefix("ComponentId(")
+            .and_then(|id| id.strip_suffix(')'))
+            .unwrap_or(s);
+        id.trim().parse().map(ComponentIdArg)
+    }
+}
+
+/// The component ids given to `name`, see [`ComponentIdArg`].
+fn component_ids(matches: &ArgMatches, name: &str) -> Result<Vec<usize>, clap::Error> {
+    let ids = matches.values_of_t::<ComponentIdArg>(name)?;
+    Ok(ids.into_iter().map(|id| id.0).collect())
+}
+
+/// Spaces to indent JSON output with, from `--indent <N>` or 2 for `--pretty`, `None` for compact.
+pub fn json_indent(matches: &ArgMatches) -> Option<usize> {
+    match matches.value_of_t("indent") {
+        Ok(indent) => Some(indent),
+        Err(_) if matches.is_present("pretty") => Some(2),
+        Err(_) => None,
+    }
+}
+
+/// Renders JSON on one line, or pretty printed when `indent` is set.
+pub fn fmt_json(value: &serde_json::Value, indent: Option<usize>) -> String {
+    let indent = match indent {
+        Some(indent) => " ".repeat(indent),
+        None => return value.to_string(),
+    };
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+    value.serialize(&mut serializer).unwrap();
+
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Which resources `resources list` prints, narrowed by `--present`/`--absent` to the ones that
+/// do or don't hold a value and by `--send`/`--non-send`/`--with-reflect` to the `Send` or
+/// reflected ones.
+#[derive(Default)]
+pub struct ResourceFilter {
+    /// only resources that hold a value (`true`) or only ones that don't (`false`)
+    pub present: Option<bool>,
+    /// only `Send` resources (`true`) or only ones added with `insert_non_send_resource` (`false`)
+    pub send: Option<bool>,
+    /// only resources registered in the `TypeRegistry`, which the console can reflect on
+    pub with_reflect: bool,
+}
+
+/// The crate a type comes from, i.e. `bevy_time` for `bevy_time::time::Time`.
+fn crate_name(full_name: &str) -> &str {
+    // the crate of a generic type is the one of the outer type
+    let path = full_name.split('<').next().unwrap_or(full_name);
+    match path.split_once("::") {
+        Some((crate_name, _)) => crate_name,
+        None => "(no crate)",
+    }
+}
+
+pub fn list_resources(
+    world: &World,
+    name_style: NameStyle,
+    filter: ResourceFilter,
+    by_crate: bool,
+    header_style: HeaderStyle,
+) -> String {
     let mut output = String::new();
+    let has_value = |id: &ComponentId| world.get_resource_by_id(*id).is_some();
+    let registry = world.get_resource::<TypeRegistry>().map(|registry| registry.read());
+    let is_reflected = |info: &ComponentInfo| match (&registry, info.type_id()) {
+        (Some(registry), Some(type_id)) => registry.get(type_id).is_some(),
+        _ => false,
+    };
 
-    let mut r: Vec<String> = archetypes
+    let total = world.archetypes().resource().components().count();
+    let mut r: Vec<(&str, String)> = world
+        .archetypes()
         .resource()
         .components()
-        .map(|id| components.get_info(id).unwrap())
-        // get_short_name removes the path information
-        // i.e. `bevy_audio::audio::Audio` -> `Audio`
-        // if you want to see the path info replace
-        // `get_short_name` with `String::from`
-        .map(|info| get_short_name(info.name()))
+        .filter(|id| match filter.present {
+            Some(present) => has_value(id) == present,
+            None => true,
+        })
+        .map(|id| world.components().get_info(id).unwrap())
+        .filter(|info| match filter.send {
+            Some(send) => info.is_send_and_sync() == send,
+            None => true,
+        })
+        .filter(|info| !filter.with_reflect || is_reflected(info))
+        .map(|info| (crate_name(info.name()), type_name(info.name(), name_style)))
         .collect();
 
-    // sort list alphebetically
+    // sort list alphebetically, by crate first
     r.sort();
 
-    writeln!(output, "[resource name]").unwrap();
-    r.iter()
+    if by_crate {
+        let mut crates: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+        r.iter()
+            .for_each(|(crate_name, name)| crates.entry(crate_name).or_default().push(name));
+        write_header(&mut output, header_style, &["crate", "resource count"]);
+        crates.iter().for_each(|(crate_name, names)| {
+            writeln!(output, "{} {}", crate_name, names.len()).unwrap();
+            names
+                .iter()
+                .for_each(|name| writeln!(output, "    {}", name).unwrap());
+        });
+        output.push_str(&count_footer(r.len(), total, "resources"));
+        return output;
+    }
+
+    let mut names: Vec<&String> = r.iter().map(|(_, name)| name).collect();
+    names.sort();
+    write_header(&mut output, header_style, &["resource name"]);
+    names
+        .iter()
         .for_each(|name| writeln!(output, "{}", name).unwrap());
+    output.push_str(&count_footer(names.len(), total, "resources"));
 
     output
 }
 
 fn get_components_by_name(
     components: &Components,
-    short: bool,
+    name_style: NameStyle,
     filter: Option<&str>,
 ) -> Vec<(usize, String)> {
     let mut names = Vec::new();
-    for id in 1..components.len() {
+    for id in 0..components.len() {
         if let Some(info) = components.get_info(ComponentId::new(id)) {
-            if short {
-                names.push((id, get_short_name(info.name())));
-            } else {
-                names.push((id, String::from(info.name())));
-            }
+            names.push((id, type_name(info.name(), name_style)));
         }
     }
 
@@ -61,181 +404,1860 @@ fn get_components_by_name(
     }
 }
 
-fn list_components(c: &Components, short: bool, filter: Option<&str>) -> String {
-    let mut names = get_components_by_name(c, short, filter);
-    names.sort();
+/// Old component names mapped to the new ones by `components remap`. Looking a component up by
+/// a name that matches nothing falls back to these, so scripts keep working through a rename.
+#[derive(Default)]
+pub struct TypeNameRemapper(pub BTreeMap<String, String>);
+
+/// `name`, or the name it was remapped to when no component matches it, along with a notice
+/// telling to use the new name.
+fn resolve_renamed<'a>(world: &'a World, name: &'a str) -> (&'a str, String) {
+    if !get_components_by_name(world.components(), NameStyle::Full, Some(name)).is_empty() {
+        return (name, String::new());
+    }
+
+    let renamed = world.get_resource::<TypeNameRemapper>().and_then(|remapper| {
+        remapper
+            .0
+            .iter()
+            .find(|(old, _)| *old == name || get_short_name(old) == name)
+    });
+    match renamed {
+        Some((old, new)) => (
+            new.as_str(),
+            format!("{} is deprecated, it was renamed to {}\n", old, new),
+        ),
+        None => (name, String::new()),
+    }
+}
 
+fn remap_component_name(world: &mut World, from: &str, to: &str) -> String {
     let mut output = String::new();
-    writeln!(output, "[component id] [component name]").unwrap();
-    names
-        .iter()
-        .for_each(|(id, name)| writeln!(output, "{} {}", id, name).unwrap());
+    if get_components_by_name(world.components(), NameStyle::Full, Some(to)).is_empty() {
+        writeln!(output, "warning: no component matches {} yet", to).unwrap();
+    }
+    world
+        .get_resource_or_insert_with(TypeNameRemapper::default)
+        .0
+        .insert(String::from(from), String::from(to));
+    writeln!(output, "{} now looks up {}.", from, to).unwrap();
 
     output
 }
 
-fn list_entities(e: &Entities) -> String {
-    let mut output = String::new();
-    writeln!(output, "[entity index] [archetype id]").unwrap();
-    for id in 0..e.len() {
-        if let Some(entity) = e.resolve_from_id(id) {
-            if let Some(location) = e.get(entity) {
-                writeln!(output, "{} {}", id, location.archetype_id.index()).unwrap();
-            }
+/// Resolves `name` by first looking for components whose short name or full path is exactly
+/// `name`, and only when there are none falls back to every component whose path contains it.
+/// So `Transform` resolves to `Transform` alone even though `GlobalTransform` contains it too.
+fn get_components_by_name_smart(c: &Components, name: &str) -> Vec<(usize, String)> {
+    let exact: Vec<(usize, String)> = get_components_by_name(c, NameStyle::Full, None)
+        .into_iter()
+        .filter(|(_, full_name)| full_name == name || get_short_name(full_name) == name)
+        .collect();
+
+    if exact.is_empty() {
+        get_components_by_name(c, NameStyle::Full, Some(name))
+    } else {
+        exact
+    }
+}
+
+/// Which components `components list` prints.
+struct ComponentFilter<'a> {
+    /// substring of the printed name
+    contains: Option<&'a str>,
+    /// substrings of the printed name to leave out, i.e. `Handle`
+    exclude: Vec<&'a str>,
+    /// crate or module the full path has to start with, i.e. `bevy_transform`
+    path_prefix: Option<&'a str>,
+    /// lowest component id, to only list the ones registered since a snapshot
+    since: usize,
+    /// components of the archetypes given with `--archetype`
+    archetypes: Option<BTreeSet<ComponentId>>,
+    /// components in at least one archetype, set with `--in-use`
+    in_use: Option<HashSet<ComponentId>>,
+    /// window of component ids, set with `--id-range`
+    id_range: Option<Range<usize>>,
+}
+
+impl ComponentFilter<'_> {
+    fn matches(&self, c: &Components, id: usize, name: &str) -> bool {
+        if self.exclude.iter().any(|exclude| name.contains(exclude)) {
+            return false;
+        }
+        if matches!(&self.archetypes, Some(ids) if !ids.contains(&ComponentId::new(id))) {
+            return false;
+        }
+        if matches!(&self.in_use, Some(ids) if !ids.contains(&ComponentId::new(id))) {
+            return false;
+        }
+        if matches!(&self.id_range, Some(range) if !range.contains(&id)) {
+            return false;
         }
+
+        let prefix = match self.path_prefix {
+            Some(prefix) => format!("{}::", prefix.trim_end_matches("::")),
+            None => return id >= self.since,
+        };
+        id >= self.since
+            && matches!(c.get_info(ComponentId::new(id)), Some(info) if info.name().starts_with(&prefix))
     }
+}
 
-    output
+/// Parses `a..b`, `a..=b`, `a..` or `..b` into the ids it covers.
+fn parse_id_range(range: &str) -> Result<Range<usize>, String> {
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| format!("--id-range: expected a range like 100..120, got {}\n", range))?;
+    let parse = |bound: &str, default: usize| match bound {
+        "" => Ok(default),
+        bound => bound
+            .parse::<usize>()
+            .map_err(|e| format!("--id-range: {} in {}\n", e, range)),
+    };
+    let start = parse(start, 0)?;
+    let end = match end.strip_prefix('=') {
+        Some(end) => parse(end, usize::MAX)?.saturating_add(1),
+        None => parse(end, usize::MAX)?,
+    };
+
+    Ok(start..end)
+}
+
+/// The components in any of the archetypes with the given ids.
+fn archetype_components(a: &Archetypes, ids: &[usize]) -> Result<BTreeSet<ComponentId>, String> {
+    let mut components = BTreeSet::new();
+    for id in ids {
+        match a.get(ArchetypeId::new(*id)) {
+            Some(archetype) => components.extend(archetype.components()),
+            None => return Err(format!("No archetype found with id: {}\n", id)),
+        }
+    }
+
+    Ok(components)
+}
+
+/// The columns of `components list`.
+#[derive(Clone, Copy)]
+struct ComponentColumns {
+    /// add a size column and sort by it, largest first
+    sizes: bool,
+    raw_bytes: bool,
+    raw_ids: bool,
+    header_style: HeaderStyle,
 }
 
-fn list_archetypes(a: &Archetypes) -> String {
+fn list_components(
+    c: &Components,
+    name_style: NameStyle,
+    filter: ComponentFilter,
+    columns: ComponentColumns,
+    pagination: Pagination,
+) -> String {
+    let ComponentColumns {
+        sizes: sort_by_size,
+        raw_bytes,
+        raw_ids,
+        header_style,
+    } = columns;
+    let mut names = get_components_by_name(c, name_style, filter.contains);
+    names.retain(|(id, name)| filter.matches(c, *id, name));
+    names.sort();
+
+    let size = |id: usize| {
+        c.get_info(ComponentId::new(id))
+            .map_or(0, |info| info.layout().size())
+    };
+    if sort_by_size {
+        // stable sort, so components of equal size stay sorted by name
+        names.sort_by_key(|(id, _)| std::cmp::Reverse(size(*id)));
+    }
+    let count = count_footer(names.len(), c.len(), "components");
+    let (names, footer) = pagination.apply(&names);
+
     let mut output = String::new();
-    writeln!(output, "[id] [entity count]").unwrap();
-    a.iter().for_each(|archetype| {
-        writeln!(output,
-            "{} {}",
-            archetype.id().index(),
-            archetype.entities().iter().count()
-        ).unwrap()
-    });
+    if sort_by_size {
+        write_header(&mut output, header_style, &["component id", "size", "component name"]);
+        names.iter().for_each(|(id, name)| {
+            let id_text = fmt_component_id(*id, raw_ids);
+            writeln!(output, "{} {} {}", id_text, fmt_size(size(*id), raw_bytes), name).unwrap()
+        });
+    } else {
+        write_header(&mut output, header_style, &["component id", "component name"]);
+        names.iter().for_each(|(id, name)| {
+            writeln!(output, "{} {}", fmt_component_id(*id, raw_ids), name).unwrap()
+        });
+    }
+    output.push_str(&footer);
+    output.push_str(&count);
 
     output
 }
 
-fn print_ecs_counts(a: &Archetypes, c: &Components, e: &Entities) -> String {
-    format!(
-        "entities: {}, components: {}, archetypes: {}\n",
-        e.len(),
-        c.len(),
-        a.len()
-    )
+/// (component name, entity count, archetype count) for every component that isn't a resource,
+/// most used first
+fn get_component_usage(
+    a: &Archetypes,
+    c: &Components,
+    name_style: NameStyle,
+) -> Vec<(String, usize, usize)> {
+    let mut usage = vec![(0, 0); c.len()];
+    a.iter()
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+        .for_each(|archetype| {
+            archetype.components().for_each(|id| {
+                usage[id.index()].0 += archetype.entities().len();
+                usage[id.index()].1 += 1;
+            })
+        });
+
+    let resources: Vec<ComponentId> = a.resource().components().collect();
+    let mut rows: Vec<(String, usize, usize)> = (0..c.len())
+        .filter_map(|id| c.get_info(ComponentId::new(id)))
+        .filter(|info| !resources.contains(&info.id()))
+        .map(|info| {
+            let (entities, archetypes) = usage[info.id().index()];
+            (type_name(info.name(), name_style), entities, archetypes)
+        })
+        .collect();
+    rows.sort_by(|(a_name, a_count, _), (b_name, b_count, _)| {
+        b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+    });
+
+    rows
 }
 
-fn find_archetypes_by_component_name(
+fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// How `components usage` prints its rows.
+#[derive(Clone, Copy)]
+enum UsageFormat {
+    Table(HeaderStyle),
+    Csv,
+    /// the JSON indent, on one line when `None`
+    Json(Option<usize>),
+}
+
+impl UsageFormat {
+    fn from_matches(matches: &ArgMatches, header_style: HeaderStyle) -> Self {
+        if matches.is_present("json") {
+            UsageFormat::Json(json_indent(matches))
+        } else if matches.is_present("csv") {
+            UsageFormat::Csv
+        } else {
+            UsageFormat::Table(header_style)
+        }
+    }
+}
+
+fn print_component_usage(
     a: &Archetypes,
     c: &Components,
-    component_name: &str,
+    name_style: NameStyle,
+    top: Option<usize>,
+    format: UsageFormat,
 ) -> String {
-    let components = get_components_by_name(c, false, Some(component_name));
+    let mut rows = get_component_usage(a, c, name_style);
+    rows.truncate(top.unwrap_or(rows.len()));
 
-    if components.is_empty() {
-        return format!("No component found with name {}\n", component_name);
+    let mut output = String::new();
+    match format {
+        UsageFormat::Json(indent) => {
+            let rows: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|(name, entities, archetypes)| {
+                    json!({ "name": name, "entities": entities, "archetypes": archetypes })
+                })
+                .collect();
+            writeln!(output, "{}", fmt_json(&serde_json::Value::from(rows), indent)).unwrap();
+        }
+        UsageFormat::Csv => {
+            writeln!(output, "component,entities,archetypes").unwrap();
+            rows.iter().for_each(|(name, entities, archetypes)| {
+                writeln!(output, "{},{},{}", csv_field(name), entities, archetypes).unwrap()
+            });
+        }
+        UsageFormat::Table(header_style) => {
+            let columns = ["component name", "entity count", "archetype count"];
+            write_header(&mut output, header_style, &columns);
+            rows.iter().for_each(|(name, entities, archetypes)| {
+                writeln!(output, "{} {} {}", name, entities, archetypes).unwrap()
+            });
+        }
     }
 
-    if components.len() > 1 {
-        let mut output = String::new();
-        writeln!(
-            output,
-            "More than one component found with name {}",
-            component_name
-        ).unwrap();
-        writeln!(
-            output,
-            "Consider searching with '--componentid' instead\n"
-        ).unwrap();
-        writeln!(output, "[component id] [component name]").unwrap();
-        components
-            .iter()
-            .for_each(|(id, name)| writeln!(output, "{} {}", id, name).unwrap());
-        return output;
-    }
+    output
+}
 
-    if let Some(id_name) = components.get(0) {
-        return find_archetypes_by_component_id(a, id_name.0);
-    };
+/// Entity indices are reused after a despawn, so print the generation along with the index
+/// to tell the old and new entity apart, i.e. `5v1` is the second entity with index 5.
+pub fn entity_label(entity: &Entity) -> String {
+    format!("{}v{}", entity.id(), entity.generation())
+}
 
-    // should never be hit as clap
-    String::from("unsupported command")
+/// Entity indices run up to the highest one ever handed out, which is more than `Entities::len`
+/// once anything was despawned.
+fn entity_id_count(world: &World) -> u32 {
+    world.entities().meta_len() as u32
+}
+
+/// (entity, archetype id) of the entities with an index in `ids`, `named_only` skips the ones
+/// without a `Name`.
+fn get_entity_rows(world: &World, ids: Range<u32>, named_only: bool) -> Vec<(Entity, usize)> {
+    let e = world.entities();
+    ids.filter_map(|id| e.resolve_from_id(id))
+        .filter_map(|entity| e.get(entity).map(|location| (entity, location.archetype_id.index())))
+        .filter(|(entity, _)| !named_only || world.get::<Name>(*entity).is_some())
+        .collect()
 }
 
-fn find_archetypes_by_component_id(a: &Archetypes, component_id: usize) -> String {
+/// `with_name` adds a column with the `Name` of each entity, empty for the ones without.
+fn format_entity_list(
+    world: &World,
+    entities: &[(Entity, usize)],
+    with_name: bool,
+    pagination: Pagination,
+    header_style: HeaderStyle,
+) -> String {
+    let count = count_footer(entities.len(), world.entities().len() as usize, "entities");
+    let (entities, footer) = pagination.apply(entities);
+
     let mut output = String::new();
+    if with_name {
+        write_padded_header(&mut output, header_style, &["entity", "archetype id", "name"], 16);
+    } else {
+        write_header(&mut output, header_style, &["entity", "archetype id"]);
+    }
+    entities.iter().for_each(|(entity, archetype_id)| {
+        if with_name {
+            let name = world.get::<Name>(*entity).map_or("", |name| name.as_str());
+            let row = format!("{:<16}{:<16}{}", entity_label(entity), archetype_id, name);
+            writeln!(output, "{}", row.trim_end()).unwrap()
+        } else {
+            writeln!(output, "{} {}", entity_label(entity), archetype_id).unwrap()
+        }
+    });
+    output.push_str(&footer);
+    output.push_str(&count);
+
+    output
+}
+
+/// `named_only` skips entities without a `Name`, `with_name` prints the names.
+fn list_entities(
+    world: &World,
+    named_only: bool,
+    with_name: bool,
+    pagination: Pagination,
+    header_style: HeaderStyle,
+) -> String {
+    let entities = get_entity_rows(world, 0..entity_id_count(world), named_only);
+    format_entity_list(world, &entities, with_name, pagination, header_style)
+}
 
-    let archetypes = a
+/// Entities as an indented tree following `Children`, starting from the ones without a `Parent`.
+/// Entities the tree doesn't reach, i.e. with a `Parent` that doesn't exist, are listed after
+/// it with their children.
+fn list_entities_tree(world: &World) -> String {
+    // every spawned entity is in an archetype, in index order the tree follows spawn order
+    let mut entities: Vec<Entity> = world
+        .archetypes()
         .iter()
-        .filter(|archetype| archetype.components().any(|c| c.index() == component_id))
-        .map(|archetype| archetype.id().index());
+        .flat_map(|archetype| archetype.entities())
+        .copied()
+        .collect();
+    entities.sort_by_key(|entity| entity.id());
 
-    writeln!(output, "archetype ids:").unwrap();
-    archetypes.for_each(|id| write!(output, "{}, ", id).unwrap());
-    output.push('\n');
+    let mut output = String::new();
+    // a broken hierarchy can loop, so every entity is printed once at most
+    let mut printed = HashSet::new();
+    entities
+        .iter()
+        .filter(|entity| world.get::<Parent>(**entity).is_none())
+        .for_each(|entity| write_entity_tree(world, *entity, 0, &mut printed, &mut output));
+
+    let orphans: Vec<&Entity> = entities
+        .iter()
+        .filter(|entity| !printed.contains(*entity))
+        .collect();
+    if !orphans.is_empty() {
+        writeln!(output, "Orphans:").unwrap();
+        orphans
+            .into_iter()
+            .for_each(|entity| write_entity_tree(world, *entity, 0, &mut printed, &mut output));
+    }
 
     output
 }
 
-pub fn get_archetype_id_by_entity_id(a: &Archetypes, entity_id: u32) -> Option<usize> {
-    let mut archetypes = a
-        .iter()
-        .filter(|archetype| archetype.entities().iter().any(|e| e.id() == entity_id))
-        .map(|archetype| archetype.id().index());
+fn write_entity_tree(
+    world: &World,
+    entity: Entity,
+    depth: usize,
+    printed: &mut HashSet<Entity>,
+    output: &mut String,
+) {
+    if !printed.insert(entity) {
+        return;
+    }
+    let indent = "  ".repeat(depth);
+    match world.get::<Name>(entity) {
+        Some(name) => writeln!(output, "{}{} {}", indent, entity_label(&entity), name.as_str()),
+        None => writeln!(output, "{}{}", indent, entity_label(&entity)),
+    }
+    .unwrap();
 
-    archetypes.next()
+    if let Some(children) = world.get::<Children>(entity) {
+        children
+            .iter()
+            .filter(|child| world.get_entity(**child).is_some())
+            .for_each(|child| write_entity_tree(world, *child, depth + 1, printed, output));
+    }
 }
 
-fn find_archetype_by_entity_id(a: &Archetypes, entity_id: u32) -> String {
+/// Entities whose every component was added after `tick`, i.e. spawned since, with roughly how
+/// long ago according to the [`TickToTimeMap`].
+fn list_entities_added_since(
+    world: &World,
+    tick: u32,
+    pagination: Pagination,
+    header_style: HeaderStyle,
+) -> String {
+    let change_tick = world.read_change_tick();
+    // ticks wrap around, so one from the future would compare as long ago
+    if tick > change_tick {
+        return format!("tick {} is ahead of the current tick {}\n", tick, change_tick);
+    }
+
+    let added_since = |entity: Entity, since: u32| {
+        let location = world.entities().get(entity);
+        let found = location.and_then(|location| {
+            Some((world.archetypes().get(location.archetype_id)?, location.index))
+        });
+        let (archetype, index) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+        // an entity without components carries no ticks to tell
+        archetype.components().next().is_some()
+            && archetype.components().all(|id| {
+                matches!(
+                    get_component_ticks(world, archetype, index, id),
+                    Some(ticks) if ticks.is_added(since, change_tick)
+                )
+            })
+    };
+    let entities: Vec<(Entity, usize)> = get_entity_rows(world, 0..entity_id_count(world), false)
+        .into_iter()
+        .filter(|(entity, _)| added_since(*entity, tick))
+        .collect();
+    let (entities, footer) = pagination.apply(&entities);
+
+    let now = Instant::now();
+    let times = world.get_resource::<TickToTimeMap>();
     let mut output = String::new();
+    writeln!(output, "current tick: {}", change_tick).unwrap();
+    write_header(&mut output, header_style, &["entity", "archetype id", "spawned"]);
+    entities.iter().for_each(|(entity, archetype_id)| {
+        // frames are in tick order and the entity counts as added since every frame before its
+        // spawn, so the newest of those is found by binary search
+        let frame_start = times.and_then(|times| {
+            let frames = times
+                .0
+                .partition_point(|(frame_tick, _)| added_since(*entity, *frame_tick));
+            frames.checked_sub(1).and_then(|newest| times.0.get(newest))
+        });
+        let age = match frame_start {
+            Some((_, time)) => format!("< {} ms ago", (now - *time).as_millis()),
+            None => String::from("unknown"),
+        };
+        writeln!(output, "{} {} {}", entity_label(entity), archetype_id, age).unwrap()
+    });
+    output.push_str(&footer);
 
-    let archetype_id = get_archetype_id_by_entity_id(a, entity_id);
+    output
+}
 
-    writeln!(output, "archetype id:").unwrap();
-    if let Some(id) = archetype_id {
-        writeln!(output, "{}", id).unwrap()
+/// An `entities list --chunk-size <N>` that is built over several frames, so listing millions
+/// of entities doesn't stall one frame. Driven by [`continue_list_operation`].
+pub struct PendingListOperation {
+    next_id: u32,
+    chunk_size: u32,
+    named_only: bool,
+    with_name: bool,
+    pagination: Pagination,
+    header_style: HeaderStyle,
+    entities: Vec<(Entity, usize)>,
+}
+
+pub enum ListProgress {
+    /// percent of the entities looked at so far
+    Loading(u32),
+    /// the finished listing
+    Done(String),
+}
+
+fn start_list_operation(
+    world: &mut World,
+    chunk_size: u32,
+    named_only: bool,
+    with_name: bool,
+    pagination: Pagination,
+    header_style: HeaderStyle,
+) -> String {
+    world.insert_resource(PendingListOperation {
+        next_id: 0,
+        chunk_size: chunk_size.max(1),
+        named_only,
+        with_name,
+        pagination,
+        header_style,
+        entities: Vec::new(),
+    });
+
+    String::from("Loading… (0% complete)\n")
+}
+
+/// Lists the next chunk of entities for a pending `entities list --chunk-size <N>`. Call it
+/// once a frame, it returns `None` when nothing is pending.
+pub fn continue_list_operation(world: &mut World) -> Option<ListProgress> {
+    let mut pending = world.remove_resource::<PendingListOperation>()?;
+    let total = entity_id_count(world);
+    let end = pending.next_id.saturating_add(pending.chunk_size).min(total);
+    let mut chunk = get_entity_rows(world, pending.next_id..end, pending.named_only);
+    pending.entities.append(&mut chunk);
+    pending.next_id = end;
+
+    if end >= total {
+        let output = format_entity_list(
+            world,
+            &pending.entities,
+            pending.with_name,
+            pending.pagination,
+            pending.header_style,
+        );
+        return Some(ListProgress::Done(output));
     }
 
-    output
+    let percent = (u64::from(end) * 100 / u64::from(total)) as u32;
+    world.insert_resource(pending);
+    Some(ListProgress::Loading(percent))
 }
 
-fn find_entities_by_component_id(a: &Archetypes, component_id: usize) -> String {
-    let entities: Vec<&Entity> = a
-        .iter()
-        .filter(|archetype| archetype.components().any(|c| c.index() == component_id))
+/// Columns `archetypes list --columns` can pick from, with their headers.
+const ARCHETYPE_COLUMNS: [(&str, &str); 4] = [
+    ("id", "id"),
+    ("entities", "entity count"),
+    ("table", "table id"),
+    ("components", "component count"),
+];
+
+/// Splits a `--columns` list, checking every name is in [`ARCHETYPE_COLUMNS`].
+fn parse_archetype_columns(columns: &str) -> Result<Vec<&str>, String> {
+    let names: Vec<&str> = ARCHETYPE_COLUMNS.iter().map(|(name, _)| *name).collect();
+    let columns: Vec<&str> = columns
+        .split(',')
+        .map(str::trim)
+        .filter(|column| !column.is_empty())
+        .map(|column| {
+            if names.contains(&column) {
+                Ok(column)
+            } else {
+                Err(format!(
+                    "unknown column {}, choose from {}\n",
+                    column,
+                    names.join(", ")
+                ))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    if columns.is_empty() {
+        return Err(format!("no columns given, choose from {}\n", names.join(", ")));
+    }
+
+    Ok(columns)
+}
+
+fn archetype_column(archetype: &Archetype, column: &str) -> String {
+    match column {
+        "id" => archetype.id().index().to_string(),
+        "entities" => archetype.entities().len().to_string(),
+        "table" => archetype.table_id().index().to_string(),
+        "components" => archetype.components().count().to_string(),
+        _ => String::new(),
+    }
+}
+
+/// `sort_by_count` orders by entity count instead of id, fewest first unless `desc`.
+/// `empty` keeps only archetypes without entities (`Some(true)`) or only the ones with some
+/// (`Some(false)`). `columns` are names from [`ARCHETYPE_COLUMNS`], printed in that order.
+fn list_archetypes(
+    a: &Archetypes,
+    sort_by_count: bool,
+    desc: bool,
+    empty: Option<bool>,
+    component_count: RangeInclusive<usize>,
+    columns: &[&str],
+    header_style: HeaderStyle,
+) -> String {
+    let mut archetypes: Vec<&Archetype> = a
+        .iter()
+        .filter(|archetype| match empty {
+            Some(empty) => archetype.entities().is_empty() == empty,
+            None => true,
+        })
+        .filter(|archetype| component_count.contains(&archetype.components().count()))
+        .collect();
+    if sort_by_count {
+        // stable sort, so archetypes with the same count stay in id order
+        if desc {
+            archetypes.sort_by_key(|archetype| std::cmp::Reverse(archetype.entities().len()));
+        } else {
+            archetypes.sort_by_key(|archetype| archetype.entities().len());
+        }
+    }
+
+    let mut output = String::new();
+    if let Some(empty) = empty {
+        let kind = if empty { "empty" } else { "non-empty" };
+        writeln!(
+            output,
+            "Showing {} {} archetypes out of {} total.",
+            archetypes.len(),
+            kind,
+            a.len()
+        )
+        .unwrap();
+    }
+    let headers: Vec<&str> = columns
+        .iter()
+        .filter_map(|column| ARCHETYPE_COLUMNS.iter().find(|(name, _)| name == column))
+        .map(|(_, header)| *header)
+        .collect();
+    write_header(&mut output, header_style, &headers);
+    archetypes.iter().for_each(|archetype| {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| archetype_column(archetype, column))
+            .collect();
+        writeln!(output, "{}", row.join(" ")).unwrap()
+    });
+    output.push_str(&count_footer(archetypes.len(), a.len(), "archetypes"));
+
+    output
+}
+
+fn print_archetypes_table(a: &Archetypes, header_style: HeaderStyle) -> String {
+    let mut output = String::new();
+    let columns = ["id", "table id", "entity count", "component count"];
+    write_header(&mut output, header_style, &columns);
+    a.iter().for_each(|archetype| {
+        writeln!(
+            output,
+            "{} {} {} {}",
+            archetype.id().index(),
+            archetype.table_id().index(),
+            archetype.entities().len(),
+            archetype.components().count()
+        )
+        .unwrap()
+    });
+
+    output
+}
+
+/// Counts saved by the `snapshot` command to compare the world against later.
+#[derive(Default)]
+pub struct EcsSnapshot {
+    pub components: usize,
+    pub archetypes: usize,
+    pub entities: u32,
+}
+
+fn set_world_frozen(world: &mut World, frozen: bool) -> String {
+    let mut world_frozen = world.get_resource_or_insert_with(WorldFrozen::default);
+    if world_frozen.0 == frozen {
+        let state = if frozen { "frozen" } else { "not frozen" };
+        return format!("the world is already {}\n", state);
+    }
+    world_frozen.0 = frozen;
+    // steps left over from before shouldn't run on the next freeze
+    if let Some(mut step) = world.get_resource_mut::<StepMode>() {
+        step.steps_remaining = 0;
+    }
+
+    if frozen {
+        String::from(
+            "world frozen until 'world unfreeze'.\n\
+            Systems in CoreStage::Update stop, systems in other stages keep running unless they \
+            use the not_frozen run criteria.\n",
+        )
+    } else {
+        String::from("world unfrozen.\n")
+    }
+}
+
+fn step_world(world: &mut World, frames: u32) -> String {
+    if !matches!(world.get_resource::<WorldFrozen>(), Some(frozen) if frozen.0) {
+        return String::from("the world isn't frozen, run 'world freeze' first.\n");
+    }
+    world.get_resource_or_insert_with(StepMode::default).steps_remaining = frames;
+
+    format!("running {} frames, then the world is frozen again.\n", frames)
+}
+
+fn take_snapshot(world: &mut World) -> String {
+    // insert the resource first, so registering it doesn't count as a new component
+    world.get_resource_or_insert_with(EcsSnapshot::default);
+    let snapshot = EcsSnapshot {
+        components: world.components().len(),
+        archetypes: world.archetypes().len(),
+        entities: world.entities().len(),
+    };
+    let output = format!(
+        "saved snapshot: entities: {}, components: {}, archetypes: {}\n",
+        snapshot.entities, snapshot.components, snapshot.archetypes
+    );
+    world.insert_resource(snapshot);
+
+    output
+}
+
+/// With `detailed`, the component count is split into zero-sized markers and components
+/// carrying data, i.e. `components: 120 (45 markers, 75 data)`.
+fn print_ecs_counts(a: &Archetypes, c: &Components, e: &Entities, detailed: bool) -> String {
+    let components = if detailed {
+        let markers = (0..c.len())
+            .filter_map(|id| c.get_info(ComponentId::new(id)))
+            .filter(|info| info.layout().size() == 0)
+            .count();
+        format!("{} ({} markers, {} data)", c.len(), markers, c.len() - markers)
+    } else {
+        c.len().to_string()
+    };
+    format!(
+        "entities: {}, components: {}, archetypes: {}\n",
+        e.len(),
+        components,
+        a.len()
+    )
+}
+
+/// Whether `watch counts` is printing the counts every frame.
+#[derive(Default)]
+pub struct WatchCounts(pub bool);
+
+fn set_watch_counts(world: &mut World, watch: bool) -> String {
+    world.get_resource_or_insert_with(WatchCounts::default).0 = watch;
+    if watch {
+        String::from("printing counts every frame, 'watch stop' to end.\n")
+    } else {
+        String::from("stopped watching.\n")
+    }
+}
+
+/// Prints the counts with the frame number every frame while `watch counts` is on.
+pub fn print_watched_counts(world: &World) {
+    if !matches!(world.get_resource::<WatchCounts>(), Some(watch) if watch.0) {
+        return;
+    }
+
+    let frame = world.get_resource::<FrameCount>().map_or(0, |frames| frames.0);
+    let counts =
+        print_ecs_counts(world.archetypes(), world.components(), world.entities(), false);
+    print!("frame {}: {}", frame, counts);
+}
+
+fn print_world_tick(world: &World) -> String {
+    let mut output = String::new();
+    writeln!(output, "change tick: {}", world.read_change_tick()).unwrap();
+    // exclusive systems set it to the tick they last ran at, so here it's the console's last run
+    writeln!(output, "last change tick: {}", world.last_change_tick()).unwrap();
+
+    output
+}
+
+fn print_world_summary(
+    world: &World,
+    name_style: NameStyle,
+    raw_ids: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let a = world.archetypes();
+    let c = world.components();
+    let name = |id: ComponentId| {
+        c.get_info(id)
+            .map(|info| type_name(info.name(), name_style))
+            .unwrap_or_default()
+    };
+
+    // the empty and resource archetypes always exist, leave them out of the archetype sections
+    let archetypes: Vec<&Archetype> = a
+        .iter()
+        .filter(|archetype| archetype.id() != ArchetypeId::EMPTY)
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+        .collect();
+    let mut resources: Vec<String> = a.resource().components().map(name).collect();
+    resources.sort();
+
+    let mut output = String::new();
+    writeln!(output, "{}\n", versions()).unwrap();
+    writeln!(output, "== totals ==").unwrap();
+    writeln!(output, "{:<12}{}", "entities:", world.entities().len()).unwrap();
+    writeln!(output, "{:<12}{}", "components:", c.len()).unwrap();
+    writeln!(output, "{:<12}{}", "archetypes:", a.len()).unwrap();
+    writeln!(output, "{:<12}{}", "resources:", resources.len()).unwrap();
+
+    let mut largest = archetypes.clone();
+    largest.sort_by_key(|archetype| std::cmp::Reverse(archetype.entities().len()));
+    writeln!(output, "\n== top 5 archetypes by entity count ==").unwrap();
+    let columns = ["archetype id", "entity count", "components"];
+    write_padded_header(&mut output, header_style, &columns, 16);
+    largest.iter().take(5).for_each(|archetype| {
+        let components: Vec<String> = archetype.components().map(name).collect();
+        writeln!(
+            output,
+            "{:<16}{:<16}{}",
+            archetype.id().index(),
+            archetype.entities().len(),
+            components.join(", ")
+        )
+        .unwrap()
+    });
+
+    let mut coverage = vec![0; c.len()];
+    archetypes.iter().for_each(|archetype| {
+        archetype
+            .components()
+            .for_each(|id| coverage[id.index()] += 1)
+    });
+    let mut coverage: Vec<(usize, usize)> = coverage
+        .into_iter()
+        .enumerate()
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    coverage.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    writeln!(output, "\n== top 5 components by archetype coverage ==").unwrap();
+    let columns = ["component id", "archetypes", "component name"];
+    write_padded_header(&mut output, header_style, &columns, 16);
+    coverage.iter().take(5).for_each(|(id, count)| {
+        let id_text = fmt_component_id(*id, raw_ids);
+        writeln!(output, "{:<16}{:<16}{}", id_text, count, name(ComponentId::new(*id))).unwrap()
+    });
+
+    let empty: Vec<String> = archetypes
+        .iter()
+        .filter(|archetype| archetype.entities().is_empty())
+        .map(|archetype| archetype.id().index().to_string())
+        .collect();
+    writeln!(output, "\n== archetypes with no entities ==").unwrap();
+    if empty.is_empty() {
+        writeln!(output, "none").unwrap();
+    } else {
+        writeln!(output, "{}", empty.join(", ")).unwrap();
+    }
+
+    writeln!(output, "\n== resources ==").unwrap();
+    resources
+        .iter()
+        .for_each(|resource| writeln!(output, "{}", resource).unwrap());
+
+    output
+}
+
+fn find_archetypes_by_component_name(
+    a: &Archetypes,
+    c: &Components,
+    component_name: &str,
+    raw_ids: bool,
+    only_ids: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let components = get_components_by_name(c, NameStyle::Full, Some(component_name));
+
+    if components.is_empty() {
+        return format!("No component found with name {}\n", component_name);
+    }
+
+    if components.len() > 1 {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "More than one component found with name {}",
+            component_name
+        ).unwrap();
+        writeln!(
+            output,
+            "Consider searching with '--componentid' instead\n"
+        ).unwrap();
+        write_header(&mut output, header_style, &["component id", "component name"]);
+        components.iter().for_each(|(id, name)| {
+            writeln!(output, "{} {}", fmt_component_id(*id, raw_ids), name).unwrap()
+        });
+        return output;
+    }
+
+    if let Some(id_name) = components.get(0) {
+        return find_archetypes_by_component_id(a, id_name.0, only_ids);
+    };
+
+    // should never be hit as clap
+    String::from("unsupported command")
+}
+
+fn find_archetypes_by_component_id(a: &Archetypes, component_id: usize, only_ids: bool) -> String {
+    let mut output = String::new();
+
+    let archetypes: Vec<String> = a
+        .iter()
+        .filter(|archetype| archetype.components().any(|c| c.index() == component_id))
+        .map(|archetype| archetype.id().index().to_string())
+        .collect();
+
+    if only_ids {
+        archetypes.iter().for_each(|id| writeln!(output, "{}", id).unwrap());
+        return output;
+    }
+    writeln!(output, "archetype ids:").unwrap();
+    writeln!(output, "{}", archetypes.join(", ")).unwrap();
+
+    output
+}
+
+/// Archetypes without components and their entities, i.e. spawned with nothing inserted.
+fn find_archetypes_without_components(
+    a: &Archetypes,
+    only_ids: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let archetypes: Vec<&Archetype> = a
+        .iter()
+        // resources live in an archetype of their own, without regular components
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+        .filter(|archetype| archetype.components().next().is_none())
+        .collect();
+
+    let mut output = String::new();
+    if only_ids {
+        archetypes
+            .iter()
+            .for_each(|archetype| writeln!(output, "{}", archetype.id().index()).unwrap());
+        return output;
+    }
+    if archetypes.is_empty() {
+        writeln!(output, "no archetypes without components found").unwrap();
+        return output;
+    }
+
+    write_header(&mut output, header_style, &["archetype id", "entity count"]);
+    archetypes.iter().for_each(|archetype| {
+        writeln!(output, "{} {}", archetype.id().index(), archetype.entities().len()).unwrap()
+    });
+    let entities: Vec<String> = archetypes
+        .iter()
         .flat_map(|archetype| archetype.entities())
+        .map(entity_label)
         .collect();
+    if !entities.is_empty() {
+        writeln!(output, "entities:").unwrap();
+        writeln!(output, "{}", entities.join(", ")).unwrap();
+    }
+
+    output
+}
+
+pub fn get_archetype_id_by_entity_id(a: &Archetypes, entity_id: u32) -> Option<usize> {
+    let mut archetypes = a
+        .iter()
+        .filter(|archetype| archetype.entities().iter().any(|e| e.id() == entity_id))
+        .map(|archetype| archetype.id().index());
+
+    archetypes.next()
+}
+
+fn get_archetype_component_names(
+    a: &Archetypes,
+    c: &Components,
+    archetype_id: ArchetypeId,
+    name_style: NameStyle,
+) -> Option<Vec<String>> {
+    let mut names: Vec<String> = a
+        .get(archetype_id)?
+        .components()
+        .filter_map(|id| c.get_info(id))
+        .map(|info| type_name(info.name(), name_style))
+        .collect();
+    names.sort();
+
+    Some(names)
+}
+
+/// Empty for a stale `entity`, `Entities::get` checks the generation before giving the location.
+fn get_entity_component_names(
+    a: &Archetypes,
+    c: &Components,
+    e: &Entities,
+    entity: Entity,
+    name_style: NameStyle,
+) -> Vec<String> {
+    e.get(entity)
+        .and_then(|location| get_archetype_component_names(a, c, location.archetype_id, name_style))
+        .unwrap_or_default()
+}
+
+/// Splits two sets of names into (only in `names1`, only in `names2`, in both)
+fn diff_names(
+    names1: Vec<String>,
+    names2: Vec<String>,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let (both, only1): (Vec<String>, Vec<String>) =
+        names1.into_iter().partition(|name| names2.contains(name));
+    let only2 = names2
+        .into_iter()
+        .filter(|name| !both.contains(name))
+        .collect();
+
+    (only1, only2, both)
+}
+
+fn write_diff(
+    output: &mut String,
+    kind: &str,
+    id1: impl std::fmt::Display,
+    id2: impl std::fmt::Display,
+    only1: &[String],
+    only2: &[String],
+    both: &[String],
+) {
+    for (header, names) in [
+        (format!("Only on {} {}:", kind, id1), only1),
+        (format!("Only on {} {}:", kind, id2), only2),
+        (String::from("On both:"), both),
+    ] {
+        writeln!(output, "{}", header).unwrap();
+        names
+            .iter()
+            .for_each(|name| writeln!(output, "  {}", name).unwrap());
+    }
+}
+
+/// Component names only on `e1`, only on `e2` and on both
+pub fn diff_entity_components(
+    a: &Archetypes,
+    c: &Components,
+    e: &Entities,
+    e1: Entity,
+    e2: Entity,
+    name_style: NameStyle,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    diff_names(
+        get_entity_component_names(a, c, e, e1, name_style),
+        get_entity_component_names(a, c, e, e2, name_style),
+    )
+}
+
+fn resolve_entity(e: &Entities, entity_id: u32) -> Option<Entity> {
+    e.resolve_from_id(entity_id)
+        .filter(|entity| e.get(*entity).is_some())
+}
+
+fn print_entity(
+    a: &Archetypes,
+    c: &Components,
+    e: &Entities,
+    entity_id: u32,
+    name_style: NameStyle,
+    component_ids: bool,
+    raw_ids: bool,
+) -> String {
+    let entity = match e.resolve_from_id(entity_id) {
+        Some(entity) => entity,
+        None => return format!("No entity found with id: {}\n", entity_id),
+    };
+    // reserved entities, i.e. from `Commands::spawn`, only get a location once commands are applied
+    // and lie past `meta_len` until then, despawned ones have no location but keep their slot
+    let location = match e.get(entity) {
+        Some(location) => location,
+        None if (entity_id as usize) < e.meta_len() => {
+            return format!("No entity found with id: {}\n", entity_id)
+        }
+        None => {
+            return format!(
+                "entity {} exists but has no archetype/location yet, it is spawned when commands are applied\n",
+                entity_label(&entity)
+            )
+        }
+    };
+
+    let mut output = String::new();
+    writeln!(output, "id: {}", entity_label(&entity)).unwrap();
+    writeln!(output, "archetype id: {}", location.archetype_id.index()).unwrap();
+    if component_ids {
+        let mut components: Vec<(usize, String)> = a
+            .get(location.archetype_id)
+            .into_iter()
+            .flat_map(|archetype| archetype.components())
+            .filter_map(|id| c.get_info(id))
+            .map(|info| (info.id().index(), type_name(info.name(), name_style)))
+            .collect();
+        components.sort_by(|a, b| a.1.cmp(&b.1));
+        writeln!(output, "components ({}):", components.len()).unwrap();
+        components.iter().for_each(|(id, name)| {
+            writeln!(output, "{} {}", fmt_component_id(*id, raw_ids), name).unwrap()
+        });
+        return output;
+    }
+
+    let names = get_entity_component_names(a, c, e, entity, name_style);
+    writeln!(output, "components ({}):", names.len()).unwrap();
+    names
+        .iter()
+        .for_each(|name| writeln!(output, "{}", name).unwrap());
+
+    output
+}
+
+/// The components on entity `entity_id`, in the same format as `components list`.
+fn find_components_by_entity(
+    a: &Archetypes,
+    c: &Components,
+    e: &Entities,
+    entity_id: u32,
+    name_style: NameStyle,
+    raw_ids: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let location = match e.resolve_from_id(entity_id).and_then(|entity| e.get(entity)) {
+        Some(location) => location,
+        None => return format!("No entity found with id: {}\n", entity_id),
+    };
+    let mut names: Vec<(usize, String)> = match a.get(location.archetype_id) {
+        Some(archetype) => archetype
+            .components()
+            .filter_map(|id| c.get_info(id))
+            .map(|info| (info.id().index(), type_name(info.name(), name_style)))
+            .collect(),
+        None => Vec::new(),
+    };
+    names.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut output = String::new();
+    write_header(&mut output, header_style, &["component id", "component name"]);
+    names.iter().for_each(|(id, name)| {
+        writeln!(output, "{} {}", fmt_component_id(*id, raw_ids), name).unwrap()
+    });
+
+    output
+}
+
+fn print_entity_diff(
+    a: &Archetypes,
+    c: &Components,
+    e: &Entities,
+    entity_id1: u32,
+    entity_id2: u32,
+    name_style: NameStyle,
+) -> String {
+    let (e1, e2) = match (resolve_entity(e, entity_id1), resolve_entity(e, entity_id2)) {
+        (Some(e1), Some(e2)) => (e1, e2),
+        (None, _) => return format!("No entity found with id: {}\n", entity_id1),
+        (_, None) => return format!("No entity found with id: {}\n", entity_id2),
+    };
+
+    let (only1, only2, both) = diff_entity_components(a, c, e, e1, e2, name_style);
+
+    let mut output = String::new();
+    write_diff(&mut output, "entity", entity_id1, entity_id2, &only1, &only2, &both);
+
+    output
+}
+
+/// `["A", "B", "C"]` -> `A, B and C`
+fn join_names(names: &[String]) -> String {
+    match names {
+        [] => String::from("no components"),
+        [name] => name.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn explain_archetype_diff(
+    id1: usize,
+    id2: usize,
+    only1: &[String],
+    only2: &[String],
+    both: &[String],
+) -> String {
+    let all = |only: &[String]| {
+        let mut names = [both, only].concat();
+        names.sort();
+        names
+    };
+    match (only1.is_empty(), only2.is_empty()) {
+        (true, true) => format!("Archetypes {} and {} have the same components.", id1, id2),
+        (true, false) => format!(
+            "Archetype {} has {} while archetype {} only has {}.",
+            id2,
+            join_names(&all(only2)),
+            id1,
+            join_names(both)
+        ),
+        (false, true) => format!(
+            "Archetype {} has {} while archetype {} only has {}.",
+            id1,
+            join_names(&all(only1)),
+            id2,
+            join_names(both)
+        ),
+        (false, false) => format!(
+            "Archetype {} has {} and archetype {} has {} instead, they share {}.",
+            id1,
+            join_names(only1),
+            id2,
+            join_names(only2),
+            join_names(both)
+        ),
+    }
+}
+
+/// Pairs of archetypes whose shared components make up at least `threshold` of the larger one.
+fn print_archetype_similarities(
+    a: &Archetypes,
+    c: &Components,
+    threshold: f32,
+    name_style: NameStyle,
+    suggest_merge: bool,
+) -> String {
+    let archetypes: Vec<(&Archetype, BTreeSet<ComponentId>)> = a
+        .iter()
+        .filter(|archetype| archetype.id() != ArchetypeId::EMPTY)
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+        .map(|archetype| (archetype, archetype.components().collect()))
+        .collect();
+
+    let mut output = String::new();
+    let mut pair_count = 0;
+    for (i, (archetype1, components1)) in archetypes.iter().enumerate() {
+        for (archetype2, components2) in &archetypes[i + 1..] {
+            let shared = components1.intersection(components2).count();
+            let larger = components1.len().max(components2.len());
+            let similarity = shared as f32 / larger as f32;
+            if larger == 0 || similarity < threshold {
+                continue;
+            }
+
+            let names = |archetype: &Archetype| {
+                get_archetype_component_names(a, c, archetype.id(), name_style).unwrap_or_default()
+            };
+            let (only1, only2, both) = diff_names(names(archetype1), names(archetype2));
+            let (id1, id2) = (archetype1.id().index(), archetype2.id().index());
+            writeln!(
+                output,
+                "archetypes {} and {}: {:.2} similar, {} and {} entities",
+                id1,
+                id2,
+                similarity,
+                archetype1.entities().len(),
+                archetype2.entities().len()
+            )
+            .unwrap();
+            write_diff(&mut output, "archetype", id1, id2, &only1, &only2, &both);
+            output.push('\n');
+            pair_count += 1;
+        }
+    }
+
+    if pair_count == 0 {
+        writeln!(output, "no archetypes are at least {:.2} similar", threshold).unwrap();
+    } else if suggest_merge {
+        writeln!(
+            output,
+            "Consider: if these archetypes are from add/remove cycles, use optional components instead."
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+fn print_archetype_diff(
+    a: &Archetypes,
+    c: &Components,
+    id1: usize,
+    id2: usize,
+    name_style: NameStyle,
+    explain: bool,
+) -> String {
+    let names = |id: usize| get_archetype_component_names(a, c, ArchetypeId::new(id), name_style);
+    let (names1, names2) = match (names(id1), names(id2)) {
+        (Some(names1), Some(names2)) => (names1, names2),
+        (None, _) => return format!("No archetype found with id: {}\n", id1),
+        (_, None) => return format!("No archetype found with id: {}\n", id2),
+    };
+    let entity_count = |id: usize| {
+        a.get(ArchetypeId::new(id))
+            .map_or(0, |archetype| archetype.entities().len())
+    };
+
+    let (only1, only2, both) = diff_names(names1, names2);
+
+    let mut output = String::new();
+    write_diff(&mut output, "archetype", id1, id2, &only1, &only2, &both);
+    let (count1, count2) = (entity_count(id1), entity_count(id2));
+    writeln!(
+        output,
+        "entities: {} in archetype {}, {} in archetype {} ({:+})",
+        count1,
+        id1,
+        count2,
+        id2,
+        count2 as i64 - count1 as i64
+    )
+    .unwrap();
+    if explain {
+        writeln!(
+            output,
+            "{}",
+            explain_archetype_diff(id1, id2, &only1, &only2, &both)
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+fn find_archetype_by_entity_id(a: &Archetypes, entity_id: u32) -> String {
+    let mut output = String::new();
+
+    let archetype_id = get_archetype_id_by_entity_id(a, entity_id);
+
+    writeln!(output, "archetype id:").unwrap();
+    if let Some(id) = archetype_id {
+        writeln!(output, "{}", id).unwrap()
+    }
+
+    output
+}
+
+/// Entities whose archetype has every component in `include` and none of the components in `exclude`,
+/// with the id of that archetype
+fn get_entities_by_component_ids(
+    a: &Archetypes,
+    include: &[usize],
+    exclude: &[usize],
+) -> Vec<(Entity, ArchetypeId)> {
+    let has_component =
+        |archetype: &Archetype, id: &usize| archetype.components().any(|c| c.index() == *id);
+
+    a.iter()
+        .filter(|archetype| include.iter().all(|id| has_component(archetype, id)))
+        .filter(|archetype| !exclude.iter().any(|id| has_component(archetype, id)))
+        .flat_map(|archetype| {
+            archetype
+                .entities()
+                .iter()
+                .map(|entity| (*entity, archetype.id()))
+        })
+        .collect()
+}
+
+fn find_entities_by_component_ids(
+    world: &World,
+    include: &[usize],
+    filter: FindFilter,
+    style: FoundEntities,
+    pagination: Pagination,
+) -> String {
+    let mut entities = get_entities_by_component_ids(world.archetypes(), include, filter.exclude);
+    filter.retain_named(world, &mut entities);
+    format_found_entities(&entities, style, pagination)
+}
+
+/// Which of the entities found by component `entities find` keeps.
+#[derive(Clone, Copy)]
+struct FindFilter<'a> {
+    /// component ids the entities must not have
+    exclude: &'a [usize],
+    /// drop the entities without a `Name`
+    named_only: bool,
+}
+
+impl FindFilter<'_> {
+    fn retain_named(&self, world: &World, entities: &mut Vec<(Entity, ArchetypeId)>) {
+        if self.named_only {
+            entities.retain(|(entity, _)| world.get::<Name>(*entity).is_some());
+        }
+    }
+}
+
+/// How `entities find` prints the entities it found.
+#[derive(Clone, Copy, PartialEq)]
+enum FoundEntities {
+    Labels,
+    WithArchetype,
+    Count,
+    /// one bare entity id per line and nothing else, for piping into other commands
+    Ids,
+}
+
+impl FoundEntities {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        if matches.is_present("count-only") {
+            FoundEntities::Count
+        } else if matches.is_present("only-ids") {
+            FoundEntities::Ids
+        } else if matches.is_present("with-archetype") {
+            FoundEntities::WithArchetype
+        } else {
+            FoundEntities::Labels
+        }
+    }
+}
+
+fn format_found_entities(
+    entities: &[(Entity, ArchetypeId)],
+    style: FoundEntities,
+    pagination: Pagination,
+) -> String {
+    let mut output = String::new();
+    if style == FoundEntities::Count {
+        writeln!(output, "entity count: {}", entities.len()).unwrap();
+        return output;
+    }
+
+    let mut sampled = Vec::new();
+    let entities = match pagination.sample {
+        Some((count, seed)) => {
+            sampled.extend_from_slice(entities);
+            shuffle_front(&mut sampled, count, seed);
+            sampled.truncate(count);
+            sampled.sort_by_key(|(entity, _)| entity.id());
+            if style != FoundEntities::Ids && !sampled.is_empty() {
+                writeln!(
+                    output,
+                    "random sample of {} out of {} entities, run with --seed {} for the same sample",
+                    sampled.len(),
+                    entities.len(),
+                    seed
+                )
+                .unwrap();
+            }
+            &sampled[..]
+        }
+        None => entities,
+    };
+
+    if style == FoundEntities::Ids {
+        let (entities, _) = pagination.apply(entities);
+        entities
+            .iter()
+            .for_each(|(entity, _)| writeln!(output, "{}", entity.id()).unwrap());
+        return output;
+    }
+
+    if entities.is_empty() {
+        writeln!(output, "no entites found").unwrap();
+        return output;
+    }
+
+    let (entities, footer) = pagination.apply(entities);
+    let labels: Vec<String> = entities
+        .iter()
+        .map(|(entity, archetype_id)| match style {
+            FoundEntities::WithArchetype => {
+                format!("{} ({})", entity_label(entity), archetype_id.index())
+            }
+            _ => entity_label(entity),
+        })
+        .collect();
+    writeln!(output, "entities:").unwrap();
+    writeln!(output, "{}", labels.join(", ")).unwrap();
+    output.push_str(&footer);
+
+    output
+}
+
+/// `short` matches `component_name` against the short names instead of the full paths.
+fn find_entities_by_component_name(
+    world: &World,
+    component_name: &str,
+    short: bool,
+    filter: FindFilter,
+    style: FoundEntities,
+    pagination: Pagination,
+) -> String {
+    let c = world.components();
+    let components = get_components_by_name(c, NameStyle::matching(short), Some(component_name));
+
+    let mut output = String::new();
+    components.iter().for_each(|(id, _)| {
+        if style == FoundEntities::Ids {
+            output.push_str(&find_entities_by_component_ids(world, &[*id], filter, style, pagination));
+            return;
+        }
+        // the full name, short ones can be ambiguous
+        let info = c.get_info(ComponentId::new(*id)).unwrap();
+        writeln!(output, "{}", info.name()).unwrap();
+        output.push_str(&find_entities_by_component_ids(world, &[*id], filter, style, pagination));
+        output.push('\n');
+    });
+
+    output
+}
+
+/// Entities with a component matching every one of `names`, or any of them with `any`.
+fn find_entities_by_component_names(
+    world: &World,
+    names: &[&str],
+    short: bool,
+    any: bool,
+    filter: FindFilter,
+    style: FoundEntities,
+    pagination: Pagination,
+) -> String {
+    let c = world.components();
+    let mut output = String::new();
+    // the ids each name matches, an archetype matches a name with any one of them
+    let mut id_sets: Vec<Vec<ComponentId>> = Vec::new();
+    for name in names {
+        let (name, notice) = resolve_renamed(world, name);
+        output.push_str(&notice);
+        let ids: Vec<ComponentId> = get_components_by_name(c, NameStyle::matching(short), Some(name))
+            .into_iter()
+            .map(|(id, _)| ComponentId::new(id))
+            .collect();
+        if ids.is_empty() {
+            writeln!(output, "no component found matching {}", name).unwrap();
+        }
+        id_sets.push(ids);
+    }
+
+    let a = world.archetypes();
+    let has_any =
+        |archetype: &Archetype, ids: &[ComponentId]| ids.iter().any(|id| archetype.contains(*id));
+    let matches_names = |archetype: &Archetype| {
+        if any {
+            id_sets.iter().any(|ids| has_any(archetype, ids))
+        } else {
+            id_sets.iter().all(|ids| has_any(archetype, ids))
+        }
+    };
+    // --exclude goes through the same helper as `entities list`, the names narrow it down after
+    let mut entities = get_entities_by_component_ids(a, &[], filter.exclude);
+    entities.retain(|(_, id)| matches!(a.get(*id), Some(archetype) if matches_names(archetype)));
+    filter.retain_named(world, &mut entities);
+
+    output + &format_found_entities(&entities, style, pagination)
+}
+
+/// Notes for the components matching `tag` that aren't zero-sized markers.
+fn tag_size_notes(c: &Components, tag: &str) -> String {
+    // a type used as both resource and component has two ids, note it once
+    let sizes: BTreeSet<(String, usize)> = get_components_by_name(c, NameStyle::Short, Some(tag))
+        .into_iter()
+        .filter_map(|(id, name)| Some((name, c.get_info(ComponentId::new(id))?.layout().size())))
+        .filter(|(_, size)| *size != 0)
+        .collect();
+
+    let mut output = String::new();
+    sizes.iter().for_each(|(name, size)| {
+        writeln!(
+            output,
+            "Note: {} has non-zero size ({} bytes), use --componentname for data components.",
+            name, size
+        )
+        .unwrap()
+    });
+
+    output
+}
+
+fn find_entities_by_component_count(
+    a: &Archetypes,
+    min: Option<usize>,
+    max: Option<usize>,
+    exclude: &[usize],
+    count_only: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let archetypes: Vec<&Archetype> = a
+        .iter()
+        .filter(|archetype| !archetype.entities().is_empty())
+        .filter(|archetype| {
+            let count = archetype.components().count();
+            count >= min.unwrap_or(0) && count <= max.unwrap_or(usize::MAX)
+        })
+        .filter(|archetype| {
+            !archetype
+                .components()
+                .any(|id| exclude.contains(&id.index()))
+        })
+        .collect();
+    let entity_count: usize = archetypes.iter().map(|a| a.entities().len()).sum();
+
+    let mut output = String::new();
+    if count_only {
+        writeln!(output, "entity count: {}", entity_count).unwrap();
+        return output;
+    }
+
+    if archetypes.is_empty() {
+        writeln!(output, "no entites found").unwrap();
+        return output;
+    }
+
+    let columns = ["archetype id", "component count", "entity count"];
+    write_header(&mut output, header_style, &columns);
+    archetypes.iter().for_each(|archetype| {
+        writeln!(
+            output,
+            "{} {} {}",
+            archetype.id().index(),
+            archetype.components().count(),
+            archetype.entities().len()
+        )
+        .unwrap()
+    });
+    let labels: Vec<String> = archetypes
+        .iter()
+        .flat_map(|archetype| archetype.entities())
+        .map(entity_label)
+        .collect();
+    writeln!(output, "entities:").unwrap();
+    writeln!(output, "{}", labels.join(", ")).unwrap();
+
+    output
+}
+
+/// Change ticks of component `id` on the entity at `index` in `archetype`.
+fn get_component_ticks<'w>(
+    world: &'w World,
+    archetype: &Archetype,
+    index: usize,
+    id: ComponentId,
+) -> Option<&'w ComponentTicks> {
+    // SAFETY: console commands run in an exclusive system, nothing writes the ticks meanwhile
+    match world.components().get_info(id)?.storage_type() {
+        StorageType::Table => {
+            let table = world.storages().tables.get(archetype.table_id())?;
+            let row = archetype.entity_table_row(index);
+            let ticks = table.get_column(id)?.get_ticks_slice().get(row);
+            ticks.map(|ticks| unsafe { &*ticks.get() })
+        }
+        StorageType::SparseSet => {
+            let entity = archetype.entities()[index];
+            world.storages().sparse_sets.get(id)?.get_ticks(entity).map(|ticks| unsafe { &*ticks.get() })
+        }
+    }
+}
+
+/// Counts, per component, the entities it was changed or added on after `tick`.
+fn print_changed_components(
+    world: &World,
+    tick: u32,
+    name_style: NameStyle,
+    entity_list: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let change_tick = world.read_change_tick();
+    if tick > change_tick {
+        return format!("tick {} is ahead of the current tick {}\n", tick, change_tick);
+    }
+
+    // component id -> (changed entities, added entities)
+    let mut changes: BTreeMap<ComponentId, (Vec<Entity>, Vec<Entity>)> = BTreeMap::new();
+    world.archetypes().iter().for_each(|archetype| {
+        archetype.entities().iter().enumerate().for_each(|(index, entity)| {
+            archetype.components().for_each(|id| {
+                if let Some(ticks) = get_component_ticks(world, archetype, index, id) {
+                    let changed = ticks.is_changed(tick, change_tick);
+                    let added = ticks.is_added(tick, change_tick);
+                    if changed || added {
+                        let (changed_entities, added_entities) = changes.entry(id).or_default();
+                        if changed {
+                            changed_entities.push(*entity);
+                        }
+                        if added {
+                            added_entities.push(*entity);
+                        }
+                    }
+                }
+            });
+        });
+    });
+
+    let mut changes: Vec<(String, Vec<Entity>, Vec<Entity>)> = changes
+        .into_iter()
+        .filter_map(|(id, (changed, added))| {
+            let info = world.components().get_info(id)?;
+            Some((type_name(info.name(), name_style), changed, added))
+        })
+        .collect();
+    changes.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut output = String::new();
+    writeln!(output, "current tick: {}", change_tick).unwrap();
+    if changes.is_empty() {
+        writeln!(output, "no components changed since tick {}", tick).unwrap();
+        return output;
+    }
+
+    let columns = ["component name", "changed entities", "added entities"];
+    write_header(&mut output, header_style, &columns);
+    changes.iter().for_each(|(name, changed, added)| {
+        writeln!(output, "{} {} {}", name, changed.len(), added.len()).unwrap();
+        if entity_list {
+            let labels: Vec<String> = changed.iter().map(entity_label).collect();
+            writeln!(output, "    entities: {}", labels.join(", ")).unwrap();
+        }
+    });
+
+    output
+}
+
+/// Lists entities with a component added (`added`) or changed after `tick`, and which ones.
+fn find_entities_by_tick(
+    world: &World,
+    tick: u32,
+    added: bool,
+    name_style: NameStyle,
+    exclude: &[usize],
+    count_only: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let change_tick = world.read_change_tick();
+    // ticks wrap around, so one from the future would compare as long ago
+    if tick > change_tick {
+        return format!("tick {} is ahead of the current tick {}\n", tick, change_tick);
+    }
+    let c = world.components();
+
+    let mut entities: Vec<(Entity, Vec<String>)> = Vec::new();
+    world
+        .archetypes()
+        .iter()
+        .filter(|archetype| !archetype.components().any(|id| exclude.contains(&id.index())))
+        .for_each(|archetype| {
+            archetype.entities().iter().enumerate().for_each(|(index, entity)| {
+                let names: Vec<String> = archetype
+                    .components()
+                    .filter(|id| match get_component_ticks(world, archetype, index, *id) {
+                        Some(ticks) if added => ticks.is_added(tick, change_tick),
+                        Some(ticks) => ticks.is_changed(tick, change_tick),
+                        None => false,
+                    })
+                    .filter_map(|id| c.get_info(id))
+                    .map(|info| type_name(info.name(), name_style))
+                    .collect();
+                if !names.is_empty() {
+                    entities.push((*entity, names));
+                }
+            });
+        });
+
+    let mut output = String::new();
+    if count_only {
+        writeln!(output, "entity count: {}", entities.len()).unwrap();
+        return output;
+    }
 
-    if entities.iter().len() == 0 {
-        let mut output = String::new();
+    writeln!(output, "current tick: {}", change_tick).unwrap();
+    if entities.is_empty() {
         writeln!(output, "no entites found").unwrap();
         return output;
     }
 
-    let mut output = String::new();
-    writeln!(output, "entity ids:").unwrap();
-    entities
-        .iter()
-        .for_each(|id| write!(output, "{}, ", id.id()).unwrap());
-    output.push('\n');
+    let kind = if added { "added" } else { "changed" };
+    let kind_column = format!("{} components", kind);
+    write_header(&mut output, header_style, &["entity", &kind_column]);
+    entities.iter().for_each(|(entity, names)| {
+        writeln!(output, "{} {}", entity_label(entity), names.join(", ")).unwrap()
+    });
 
     output
 }
 
-fn find_entities_by_component_name(a: &Archetypes, c: &Components, component_name: &str) -> String {
-    let components = get_components_by_name(c, false, Some(component_name));
+fn find_entities_by_name(
+    world: &World,
+    name: &str,
+    case_sensitive: bool,
+    exact: bool,
+    exclude: &[usize],
+    count_only: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let fold_case = |value: &str| {
+        if case_sensitive {
+            String::from(value)
+        } else {
+            value.to_lowercase()
+        }
+    };
+    let search = fold_case(name);
+
+    let a = world.archetypes();
+    let entities: Vec<(Entity, &Name)> = get_entities_by_component_ids(a, &[], exclude)
+        .into_iter()
+        .map(|(entity, _)| entity)
+        .filter_map(|entity| world.get::<Name>(entity).map(|name| (entity, name)))
+        .filter(|(_, name)| {
+            let name = fold_case(name.as_str());
+            if exact {
+                name == search
+            } else {
+                name.contains(&search)
+            }
+        })
+        .collect();
 
     let mut output = String::new();
-    components.iter().for_each(|(id, name)| {
-        writeln!(output, "{}", name).unwrap();
-        output.push_str(&find_entities_by_component_id(a, *id));
-        output.push('\n');
+    if count_only {
+        writeln!(output, "entity count: {}", entities.len()).unwrap();
+        return output;
+    }
+
+    if entities.is_empty() {
+        writeln!(output, "no entites found").unwrap();
+        return output;
+    }
+
+    write_header(&mut output, header_style, &["entity", "name", "archetype id"]);
+    entities.iter().for_each(|(entity, name)| {
+        let archetype_id = world
+            .entities()
+            .get(*entity)
+            .map(|location| location.archetype_id.index());
+        writeln!(
+            output,
+            "{} {} {}",
+            entity_label(entity),
+            name.as_str(),
+            archetype_id.unwrap_or_default()
+        )
+        .unwrap()
     });
 
     output
 }
 
-fn print_archetype(a: &Archetypes, c: &Components, archetype_id: ArchetypeId) -> String {
+fn print_archetype(
+    a: &Archetypes,
+    c: &Components,
+    archetype_id: ArchetypeId,
+    name_style: NameStyle,
+    related: bool,
+) -> String {
     let mut output = String::new();
     if let Some(archetype) = a.get(archetype_id) {
         writeln!(output, "id: {:?}", archetype.id()).unwrap();
         writeln!(output, "table_id: {:?}", archetype.table_id()).unwrap();
-        write!(
-            output,
-            "entities ({}): ",
-            archetype.entities().iter().count()
-        ).unwrap();
-        archetype
-            .entities()
-            .iter()
-            .for_each(|entity| writeln!(output, "{}, ", entity.id()).unwrap());
-        writeln!(output).unwrap();
+        let entities: Vec<String> = archetype.entities().iter().map(entity_label).collect();
+        writeln!(output, "entities ({}): {}", entities.len(), entities.join(", ")).unwrap();
         // not sure what entity table rows is, so commenting out for now
         // print!(
         //     "entity table rows ({}): ",
@@ -246,31 +2268,35 @@ fn print_archetype(a: &Archetypes, c: &Components, archetype_id: ArchetypeId) ->
         //     .iter()
         //     .for_each(|row| print!("{}, ", row));
         // println!("");
-        write!(
-            output,
-            "table_components ({}): ",
-            archetype.table_components().iter().count()
-        ).unwrap();
-        archetype
+        let table_components: Vec<String> = archetype
             .table_components()
             .iter()
             .map(|id| (id.index(), c.get_info(*id).unwrap()))
-            .map(|(id, info)| (id, get_short_name(info.name())))
-            .for_each(|(id, name)| write!(output, "{} {}, ", id, name).unwrap());
-        output.push('\n');
-
-        write!(
+            .map(|(id, info)| format!("{} {}", id, type_name(info.name(), name_style)))
+            .collect();
+        writeln!(
             output,
-            "sparse set components ({}): ",
-            archetype.sparse_set_components().iter().count()
+            "table_components ({}): {}",
+            table_components.len(),
+            table_components.join(", ")
         ).unwrap();
-        archetype
+
+        let sparse_set_components: Vec<String> = archetype
             .sparse_set_components()
             .iter()
             .map(|id| (id.index(), c.get_info(*id).unwrap()))
-            .map(|(id, info)| (id, get_short_name(info.name())))
-            .for_each(|(id, name)| write!(output, "{} {}, ", id, name).unwrap());
-        writeln!(output).unwrap();
+            .map(|(id, info)| format!("{} {}", id, type_name(info.name(), name_style)))
+            .collect();
+        writeln!(
+            output,
+            "sparse set components ({}): {}",
+            sparse_set_components.len(),
+            sparse_set_components.join(", ")
+        ).unwrap();
+
+        if related {
+            output.push_str(&print_related_archetypes(a, archetype));
+        }
     } else {
         writeln!(
             output,
@@ -282,17 +2308,61 @@ fn print_archetype(a: &Archetypes, c: &Components, archetype_id: ArchetypeId) ->
     output
 }
 
-fn print_component(c: &Components, component_id: usize) -> String {
+/// Other archetypes sharing at least one component with `archetype`, most shared first
+fn print_related_archetypes(a: &Archetypes, archetype: &Archetype) -> String {
+    let components: BTreeSet<ComponentId> = archetype.components().collect();
+    let mut related: Vec<(usize, usize)> = a
+        .iter()
+        .filter(|other| other.id() != archetype.id())
+        .map(|other| {
+            let shared = other
+                .components()
+                .filter(|id| components.contains(id))
+                .count();
+            (other.id().index(), shared)
+        })
+        .filter(|(_, shared)| *shared > 0)
+        .collect();
+    related.sort_by_key(|(_, shared)| std::cmp::Reverse(*shared));
+
+    let mut output = String::new();
+    let related: Vec<String> = related
+        .iter()
+        .map(|(id, shared)| format!("{} ({} shared)", id, shared))
+        .collect();
+    writeln!(output, "shares components with ({}): {}", related.len(), related.join(", ")).unwrap();
+
+    output
+}
+
+fn print_component(
+    world: &World,
+    component_id: usize,
+    raw_bytes: bool,
+    yes_no: bool,
+    raw_ids: bool,
+    entities_count: bool,
+) -> String {
+    let a = world.archetypes();
     let mut output = String::new();
-    if let Some(info) = c.get_info(ComponentId::new(component_id)) {
+    if let Some(info) = world.components().get_info(ComponentId::new(component_id)) {
         writeln!(output, "Name: {}", info.name()).unwrap();
-        writeln!(output, "Id: {}", info.id().index()).unwrap();
+        writeln!(output, "Id: {}", fmt_component_id(info.id().index(), raw_ids)).unwrap();
         write!(output, "StorageType: ").unwrap();
         match info.storage_type() {
             StorageType::Table => output.push_str("Table\n"),
             StorageType::SparseSet => output.push_str("SparseSet\n"),
         }
-        writeln!(output, "SendAndSync: {}", info.is_send_and_sync()).unwrap();
+        writeln!(output, "SendAndSync: {}", fmt_bool(info.is_send_and_sync(), yes_no)).unwrap();
+        writeln!(output, "Size: {}", fmt_size(info.layout().size(), raw_bytes)).unwrap();
+        // resources share the id space with components but never show up on entities
+        let is_resource = a.resource().components().any(|id| id == info.id());
+        writeln!(output, "IsResource: {}", fmt_bool(is_resource, yes_no)).unwrap();
+        output.push_str(&print_component_reflection(world, info));
+        if entities_count {
+            let count = count_entities_with_component(a, component_id);
+            writeln!(output, "Current entity count: {}", count).unwrap();
+        }
     } else {
         write!(output, "No component found with id: {}", component_id).unwrap();
     }
@@ -300,20 +2370,215 @@ fn print_component(c: &Components, component_id: usize) -> String {
     output
 }
 
-fn print_component_by_name(c: &Components, component_name: &str) -> String {
-    let components = get_components_by_name(c, false, Some(component_name));
+/// The reflected type data registered for `info`'s type, so it's clear whether the reflection
+/// based commands work with it.
+fn print_component_reflection(world: &World, info: &ComponentInfo) -> String {
+    let registry = world.get_resource::<TypeRegistry>().map(|registry| registry.read());
+    let registration = match (&registry, info.type_id()) {
+        (Some(registry), Some(type_id)) => registry.get(type_id),
+        _ => None,
+    };
+    let registration = match registration {
+        Some(registration) => registration,
+        None => return String::from("Reflected: not reflected\n"),
+    };
+
+    let traits: Vec<&str> = [
+        ("ReflectComponent", registration.data::<ReflectComponent>().is_some()),
+        ("ReflectDefault", registration.data::<ReflectDefault>().is_some()),
+        ("ReflectDeserialize", registration.data::<ReflectDeserialize>().is_some()),
+        ("ReflectMapEntities", registration.data::<ReflectMapEntities>().is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, registered)| *registered)
+    .map(|(name, _)| name)
+    .collect();
 
     let mut output = String::new();
-    components
-        .iter()
-        .for_each(|(id, _)| writeln!(output, "{}", &print_component(c, *id)).unwrap());
+    writeln!(output, "Reflected: {}", registration.type_name()).unwrap();
+    writeln!(output, "ReflectedTypeId: {:?}", registration.type_id()).unwrap();
+    if traits.is_empty() {
+        writeln!(output, "ReflectedTraits: none").unwrap();
+    } else {
+        writeln!(output, "ReflectedTraits: {}", traits.join(", ")).unwrap();
+    }
+
+    output
+}
+
+fn count_entities_with_component(a: &Archetypes, component_id: usize) -> usize {
+    a.iter()
+        .filter(|archetype| archetype.components().any(|id| id.index() == component_id))
+        .map(|archetype| archetype.entities().len())
+        .sum()
+}
+
+fn print_component_by_name(
+    world: &World,
+    component_name: &str,
+    smart: bool,
+    raw_bytes: bool,
+    yes_no: bool,
+    raw_ids: bool,
+) -> String {
+    let c = world.components();
+    let components = if smart {
+        get_components_by_name_smart(c, component_name)
+    } else {
+        get_components_by_name(c, NameStyle::Full, Some(component_name))
+    };
+
+    let mut output = String::new();
+    if components.len() > 1 {
+        let ids: Vec<String> = components
+            .iter()
+            .map(|(id, _)| fmt_component_id(*id, raw_ids))
+            .collect();
+        writeln!(output, "{} matches: ids [{}]\n", components.len(), ids.join(", ")).unwrap();
+    }
+    components.iter().for_each(|(id, _)| {
+        let component = print_component(world, *id, raw_bytes, yes_no, raw_ids, false);
+        writeln!(output, "{}", component).unwrap()
+    });
+
+    output
+}
+
+/// How well `term` matches `name`, from 0 to 1. Exact matches score 1, names containing the
+/// term score above 0.9 and the rest at most 0.9 by Jaro-Winkler similarity.
+fn search_score(term: &str, name: &str) -> f64 {
+    let name = name.to_lowercase();
+    if name == term {
+        1.0
+    } else if name.contains(term) {
+        0.9 + 0.1 * term.len() as f64 / name.len() as f64
+    } else {
+        0.9 * strsim::jaro_winkler(term, &name)
+    }
+}
+
+fn search(
+    world: &World,
+    term: &str,
+    min_score: f64,
+    raw_ids: bool,
+    pagination: Pagination,
+    header_style: HeaderStyle,
+) -> String {
+    let c = world.components();
+    let resources: BTreeSet<ComponentId> = world.archetypes().resource().components().collect();
+    let lowercase_term = term.to_lowercase();
+
+    let mut hits: Vec<(f64, &str, usize, String)> = (0..c.len())
+        .filter_map(|id| c.get_info(ComponentId::new(id)))
+        .map(|info| {
+            let short_name = type_name(info.name(), NameStyle::Short);
+            // the long name counts too, so crate and module names can be searched for
+            let score = search_score(&lowercase_term, &short_name)
+                .max(search_score(&lowercase_term, info.name()) - 0.05);
+            let kind = if resources.contains(&info.id()) {
+                "resource"
+            } else {
+                "component"
+            };
+            (score, kind, info.id().index(), short_name)
+        })
+        .filter(|(score, ..)| *score >= min_score)
+        .collect();
+    hits.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.3.cmp(&b.3)));
+
+    let mut output = String::new();
+    if hits.is_empty() {
+        writeln!(output, "nothing found matching {}", term).unwrap();
+        return output;
+    }
+
+    let (hits, footer) = pagination.apply(&hits);
+    write_header(&mut output, header_style, &["score", "kind", "id", "name"]);
+    hits.iter().for_each(|(score, kind, id, name)| {
+        writeln!(output, "{:.2} {} {} {}", score, kind, fmt_component_id(*id, raw_ids), name).unwrap()
+    });
+    output.push_str(&footer);
 
     output
 }
 
 pub fn build_commands(app: App) -> App {
     let app = app.subcommand(
-            App::new("counts").about("print counts of archetypes, components, and entities"),
+            App::new("counts")
+                .about("print counts of archetypes, components, and entities")
+                .arg(arg!(--detailed "split the components into zero-sized markers and data")),
+        )
+        .subcommand(
+            App::new("snapshot").about("save the current counts to compare against later"),
+        )
+        .subcommand(
+            App::new("watch")
+                .about("print information every frame")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(App::new("counts").about("print the counts with the frame number every frame"))
+                .subcommand(App::new("stop").about("stop printing every frame")),
+        )
+        .subcommand(
+            App::new("search")
+                .about("find components and resources by name, best match first")
+                .args([
+                    arg!(<Term> "part of the name, typos are fine"),
+                    arg!(--"min-score" <Score> "leave out matches scoring below Score, from 0 to 1")
+                        .required(false)
+                        .default_value("0.75"),
+                ])
+                .args(Pagination::args()),
+        )
+        .subcommand(
+            App::new("world")
+                .about("get an overview of the whole world")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(App::new("summary")
+                    .about("print totals, the largest archetypes, the most used components and all resources")
+                )
+                .subcommand(App::new("tick")
+                    .about("print the current change tick, to use with --changed-since-tick")
+                )
+                .subcommand(App::new("inspect")
+                    .about("run checks for common ECS problems, like too many archetypes")
+                )
+                .subcommand(App::new("validate")
+                    .about("check the Parent and Children components agree with each other")
+                )
+                .subcommand(App::new("freeze")
+                    .about("stop the systems in CoreStage::Update and the ones run with the not_frozen run criteria, the console keeps running")
+                )
+                .subcommand(App::new("unfreeze")
+                    .about("run the systems stopped by world freeze again")
+                )
+                .subcommand(App::new("step")
+                    .about("while frozen, run the stopped systems for Frames frames and freeze again")
+                    .arg(arg!([Frames] "number of frames to run").default_value("1"))
+                )
+                .subcommand(App::new("export")
+                    .about("write entities to a .scn.ron scene file, needs the console-scene feature")
+                    .args([
+                        arg!(--path <Path> "scene file to write, e.g. assets/scenes/snapshot.scn.ron"),
+                        arg!(--entities <Ids> "comma separated ids of the entities to export, e.g. 3,5,8")
+                            .required(false),
+                        arg!(--"all-entities" "export every entity"),
+                        arg!(--"exclude-component" <Name> "leave out the components matching Name, can be repeated")
+                            .required(false)
+                            .multiple_occurrences(true)
+                    ])
+                    .group(ArgGroup::new("selection")
+                        .args(&["entities", "all-entities"])
+                        .required(true)
+                    )
+                )
+                .subcommand(App::new("import")
+                    .about("spawn the entities of a .scn.ron scene file, needs the console-scene feature")
+                    .args([
+                        arg!(--path <Path> "scene file, e.g. assets/scenes/debug_entities.scn.ron"),
+                        arg!(--"dry-run" "only check the scene can be spawned, without spawning it")
+                    ])
+                )
         )
         .subcommand(
             App::new("archetypes")
@@ -322,24 +2587,66 @@ pub fn build_commands(app: App) -> App {
                 .setting(AppSettings::SubcommandRequiredElseHelp)
                 .subcommand(App::new("list")
                     .about("list all archetypes")
+                    .args([
+                        arg!(--"sort-by-count" "order by entity count instead of id"),
+                        arg!(--desc "list the most populated archetypes first")
+                            .requires("sort-by-count"),
+                        arg!(--empty "only list archetypes without entities"),
+                        arg!(--"non-empty" "only list archetypes with at least one entity")
+                            .conflicts_with("empty"),
+                        arg!(--"min-components" <N> "only list archetypes with at least N component types")
+                            .required(false),
+                        arg!(--"max-components" <N> "only list archetypes with at most N component types")
+                            .required(false),
+                        arg!(--columns <Columns> "comma separated columns to print in order, from id, entities, table and components")
+                            .required(false)
+                            .default_value("id,entities")
+                    ])
                 )
                 .subcommand(App::new("info")
                     .about("get info of one archetype")
-                    .arg(arg!(--id <Id> "id to get"))
+                    .args([
+                        arg!(--id <Id> "id to get"),
+                        arg!(--all "print one line per archetype instead"),
+                        arg!(--related "also list the archetypes sharing components with this one")
+                            .conflicts_with("all")
+                    ])
                     .group(ArgGroup::new("search params")
-                        .args(&["id"])
+                        .args(&["id", "all"])
                         .required(true)
                     )
                 )
+                .subcommand(App::new("diff")
+                    .about("compare the components of two archetypes")
+                    .args([
+                        arg!(--id1 <Id> "first archetype"),
+                        arg!(--id2 <Id> "second archetype"),
+                        arg!(--explain "also describe the difference in a sentence"),
+                        arg!(-l --long "display long names")
+                    ])
+                )
+                .subcommand(App::new("similarities")
+                    .about("find pairs of archetypes that share most of their components")
+                    .args([
+                        arg!(--threshold [Threshold] "0.0 to 1.0, the share of the larger archetype's components both need to have")
+                            .default_value("0.9"),
+                        arg!(--"suggest-merge" "print a hint on avoiding near identical archetypes"),
+                        arg!(-l --long "display long names")
+                    ])
+                )
                 .subcommand(App::new("find")
                     .about("find a archetype")
                     .args([
-                        arg!(--componentid <ComponentId> "find types that have components with ComponentId"),
+                        arg!(--componentid <ComponentId> "find types that have components with ComponentId")
+                            .validator(ComponentIdArg::from_str),
                         arg!(--componentname <ComponentName> "find types that have components with ComponentName"),
-                        arg!(--entityid <EntityId> "find types that have entities with EntityId")
+                        arg!(--entityid <EntityId> "find types that have entities with EntityId"),
+                        arg!(--"empty-components" "find archetypes without components, holding entities spawned empty"),
+                        arg!(--"only-ids" "print only the archetype ids, one per line")
+                            .conflicts_with("entityid")
                     ])
                     .group(ArgGroup::new("search params")
-                        .args(&["componentid", "componentname", "entityid"])
+                        .args(&["componentid", "componentname", "entityid", "empty-components"])
                         .required(true)
                     )
                 )
@@ -353,14 +2660,69 @@ pub fn build_commands(app: App) -> App {
                     .about("list all components")
                     .args([
                         arg!(-f --filter [Filter] "filter list"),
+                        arg!(--exclude <Exclude> "leave out components whose name contains Exclude, can be repeated")
+                            .required(false)
+                            .multiple_occurrences(true),
+                        arg!(--crate <Crate> "only list components whose full path starts with Crate, e.g. bevy_transform or bevy_transform::components")
+                            .required(false),
+                        arg!(-l --long "display full type paths").visible_alias("type-path"),
+                        arg!(--"sort-by-size" "add a size column and list the largest components first"),
+                        arg!(--new "only list components registered since the last snapshot"),
+                        arg!(--archetype <Id> "only list components of archetype Id, can be repeated")
+                            .required(false)
+                            .multiple_occurrences(true),
+                        arg!(--"in-use" "only list components in at least one archetype"),
+                        arg!(--"id-range" <Range> "only list components with ids in Range, e.g. 100..120, 100..=120 or 100..")
+                            .required(false)
+                    ])
+                    .args(Pagination::args())
+                )
+                .subcommand(App::new("find")
+                    .about("list the components on an entity, the inverse of entities find --componentname")
+                    .args([
+                        arg!(--entity <Id> "id of the entity"),
+                        arg!(-l --long "display long name")
+                    ])
+                )
+                .subcommand(App::new("changed")
+                    .about("list the components changed on any entity since a change tick")
+                    .args([
+                        arg!(--"since-tick" <Tick> "count changes after change tick Tick"),
+                        arg!(--"last-n-ticks" <N> "count changes in the last N change ticks"),
+                        arg!(--"entity-list" "also print the changed entities of each component"),
                         arg!(-l --long "display long name")
                     ])
+                    .group(ArgGroup::new("since")
+                        .args(&["since-tick", "last-n-ticks"])
+                        .required(true)
+                    )
+                )
+                .subcommand(App::new("remap")
+                    .about("look a renamed component up by its old name too")
+                    .args([
+                        arg!(--from <OldFullPath> "old type name, used in scripts or scenes"),
+                        arg!(--to <NewFullPath> "new type name the old one resolves to")
+                    ])
+                )
+                .subcommand(App::new("usage")
+                    .about("count the entities and archetypes each component is on")
+                    .args([
+                        arg!(--top [N] "only print the N most used components"),
+                        arg!(--csv "print as csv"),
+                        arg!(--json "print as json")
+                            .conflicts_with("csv")
+                    ])
                 )
                 .subcommand(App::new("info")
                     .about("get info of one component")
                     .args([
-                        arg!(--id <Id> "id to get"),
-                        arg!(--name <Name> "name to get")
+                        arg!(--id <Id> "id to get").validator(ComponentIdArg::from_str),
+                        arg!(--name <Name> "name to get"),
+                        arg!(--smart [Bool] "prefer exact name matches, only matching substrings when there are none")
+                            .possible_values(["true", "false"])
+                            .default_value("true"),
+                        arg!(--"entities-count" "also print how many entities have the component, with --id")
+                            .conflicts_with("name")
                     ])
                     .group(ArgGroup::new("search params")
                         .args(&["id", "name"])
@@ -371,20 +2733,102 @@ pub fn build_commands(app: App) -> App {
         .subcommand(
             App::new("entities")
                 .about("get entity info")
+                .alias("entity")
                 .setting(AppSettings::SubcommandRequiredElseHelp)
                 .subcommand(
                     App::new("list")
                         .about("list all entities")
+                        .arg(arg!(--"with-name-only" "only list entities with a Name, and print it"))
+                        .arg(arg!(--"with-name" "add a column with the Name of each entity"))
+                        .arg(
+                            arg!(--"chunk-size" <N> "look at N entities per frame instead of all at once, for huge worlds")
+                                .required(false)
+                        )
+                        .arg(
+                            arg!(--"added-since-tick" <Tick> "only list entities spawned after change tick Tick, and roughly when")
+                                .required(false)
+                                .conflicts_with_all(&["chunk-size", "with-name-only", "with-name"])
+                        )
+                        .arg(
+                            arg!(--format <Format> "list, the default, or tree to indent children under their parent and print names")
+                                .required(false)
+                                .possible_values(["list", "tree"])
+                        )
+                        .args(Pagination::args())
+                )
+                .subcommand(
+                    App::new("info")
+                        .about("get the archetype and components of one entity")
+                        .args([
+                            arg!(--id <Id> "id of the entity"),
+                            arg!(--diff [Id] "compare the components with another entity"),
+                            arg!(--"component-ids" "print the component id before each component name")
+                                .conflicts_with("diff"),
+                            arg!(-l --long "display long names")
+                        ])
                 )
                 .subcommand(
                     App::new("find")
                         .about("find entity matching search params")
                         .args([
-                            arg!(--componentid <ComponentId> "find types that have components with ComponentId"),
-                            arg!(--componentname <ComponentName> "find types that have components with ComponentName")
+                            arg!(--componentid <ComponentId> "find types that have components with ComponentId, repeat to require several")
+                                .validator(ComponentIdArg::from_str)
+                                .required(false)
+                                .multiple_occurrences(true)
+                                .conflicts_with_all(&["componentname", "name"]),
+                            arg!(--componentname <ComponentName> "find types that have components with ComponentName, repeat to require several")
+                                .required(false)
+                                .multiple_occurrences(true)
+                                .conflicts_with("name"),
+                            arg!(--any "with several --componentname, find entities with any of them instead of all")
+                                .requires("componentname"),
+                            arg!(--short "match --componentname against short names like Transform, instead of full paths like bevy_transform::components::transform::Transform")
+                                .requires("componentname"),
+                            arg!(--tag <ComponentName> "like --componentname, for zero-sized marker components")
+                                .required(false)
+                                .conflicts_with_all(&["componentid", "componentname", "name", "component-count-min", "component-count-max", "changed-since-tick", "added-since-tick"]),
+                            arg!(--name <Name> "find entities whose Name contains Name, ignoring case")
+                                .required(false),
+                            arg!(--"case-sensitive" "match --name with exact case")
+                                .requires("name"),
+                            arg!(--exact "match the whole --name instead of a substring")
+                                .requires("name"),
+                            arg!(--"component-count-min" <N> "find entities with at least N components")
+                                .required(false)
+                                .conflicts_with_all(&["componentid", "componentname", "name"]),
+                            arg!(--"component-count-max" <N> "find entities with at most N components")
+                                .required(false)
+                                .conflicts_with_all(&["componentid", "componentname", "name"]),
+                            arg!(--"changed-since-tick" <Tick> "find entities with a component changed after change tick Tick")
+                                .required(false)
+                                .conflicts_with_all(&["componentid", "componentname", "name", "component-count-min", "component-count-max"]),
+                            arg!(--"added-since-tick" <Tick> "find entities with a component added after change tick Tick")
+                                .required(false)
+                                .conflicts_with_all(&["componentid", "componentname", "name", "component-count-min", "component-count-max", "changed-since-tick"]),
+                            arg!(-l --long "display long component names"),
+                            arg!(--"not-componentid" <ComponentId> "exclude entities that have components with ComponentId, can be repeated")
+                                .validator(ComponentIdArg::from_str)
+                                .required(false)
+                                .multiple_occurrences(true),
+                            arg!(--"count-only" "only print the number of matching entities"),
+                            arg!(--"with-archetype" "print the archetype id after each entity found by --componentid, --componentname or --tag")
+                                .conflicts_with_all(&["name", "component-count-min", "component-count-max", "changed-since-tick", "added-since-tick"]),
+                            arg!(--"only-ids" "print only the ids of entities found by --componentid, --componentname or --tag, one per line")
+                                .conflicts_with_all(&["count-only", "with-archetype", "name", "component-count-min", "component-count-max", "changed-since-tick", "added-since-tick"]),
+                            arg!(--sample <N> "print N random entities found by --componentid, --componentname or --tag")
+                                .required(false)
+                                .conflicts_with_all(&["count-only", "name", "component-count-min", "component-count-max", "changed-since-tick", "added-since-tick"]),
+                            arg!(--seed <N> "seed for picking the --sample, random when not set")
+                                .required(false)
+                                .requires("sample"),
+                            arg!(--"named-only" "drop the entities without a Name from the ones found by --componentid, --componentname or --tag")
+                                .conflicts_with_all(&["component-count-min", "component-count-max", "changed-since-tick", "added-since-tick"])
                         ])
+                        .args(Pagination::args())
                         .group(ArgGroup::new("search params")
-                            .args(&["componentid", "componentname"])
+                            .args(&["componentid", "componentname", "tag", "name", "component-count-min", "component-count-max", "changed-since-tick", "added-since-tick"])
+                            // so min and max can be combined, the other params conflict with each other
+                            .multiple(true)
                             .required(true)
                         )
                 )
@@ -396,28 +2840,131 @@ pub fn build_commands(app: App) -> App {
                 .subcommand(
                     App::new("list")
                         .about("list all resources")
+                        .args([
+                            arg!(--present "only list resources that hold a value"),
+                            arg!(--absent "only list resources that are registered but hold no value")
+                                .conflicts_with("present"),
+                            arg!(--send "only list resources that are Send"),
+                            arg!(--"non-send" "only list resources added with insert_non_send_resource")
+                                .conflicts_with("send"),
+                            arg!(--"with-reflect" "only list resources registered for reflection, which the console can inspect"),
+                            arg!(--"by-crate" "group the resources under the crate they come from"),
+                            arg!(-l --long "display full type paths").visible_alias("type-path")
+                        ])
                 )
         );
 
     app
 }
 
-pub fn match_commands(
-    matches: &ArgMatches,
-    a: &Archetypes,
-    c: &Components,
-    e: &Entities,
-) -> String {
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("snapshot", _)) => return take_snapshot(world),
+        Some(("watch", matches)) => match matches.subcommand() {
+            Some(("counts", _)) => return set_watch_counts(world, true),
+            Some(("stop", _)) => return set_watch_counts(world, false),
+            _ => return String::from("this line should not be hittable"),
+        },
+        Some(("entities", matches)) => {
+            if let Some(("list", matches)) = matches.subcommand() {
+                let tree = matches.value_of("format") == Some("tree");
+                if let (Ok(chunk_size), false) = (matches.value_of_t("chunk-size"), tree) {
+                    let named_only = matches.is_present("with-name-only");
+                    let with_name = named_only || matches.is_present("with-name");
+                    let pagination = Pagination::from_matches(matches);
+                    let header_style = HeaderStyle::from_matches(matches);
+                    return start_list_operation(
+                        world,
+                        chunk_size,
+                        named_only,
+                        with_name,
+                        pagination,
+                        header_style,
+                    );
+                }
+            }
+        }
+        Some(("world", matches)) => match matches.subcommand() {
+            Some(("freeze", _)) => return set_world_frozen(world, true),
+            Some(("unfreeze", _)) => return set_world_frozen(world, false),
+            Some(("step", matches)) => match matches.value_of_t("Frames") {
+                Ok(frames) => return step_world(world, frames),
+                Err(e) => return format!("Frames: {}\n", e),
+            },
+            Some(("import", matches)) => {
+                let path = Path::new(matches.value_of("path").unwrap_or_default());
+                return import_scene(world, path, matches.is_present("dry-run"));
+            }
+            _ => {}
+        },
+        Some(("components", matches)) => {
+            if let Some(("remap", matches)) = matches.subcommand() {
+                let from = matches.value_of("from").unwrap_or_default();
+                let to = matches.value_of("to").unwrap_or_default();
+                return remap_component_name(world, from, to);
+            }
+        }
+        _ => {}
+    }
+
+    let world = &*world;
+    let a = world.archetypes();
+    let c = world.components();
+    let e = world.entities();
+    let raw_name = matches.is_present("raw-name");
+    let raw_bytes = matches.is_present("bytes");
+    let yes_no = matches.value_of("bool-style") == Some("yes-no");
+    let raw_ids = matches.value_of("component-id-base") == Some("raw");
+    let header_style = HeaderStyle::from_matches(matches);
+
     match matches.subcommand() {
         Some(("archetypes", matches)) => match matches.subcommand() {
-            Some(("list", _)) => list_archetypes(a),
+            Some(("list", matches)) => {
+                let sort_by_count = matches.is_present("sort-by-count");
+                let empty = if matches.is_present("empty") {
+                    Some(true)
+                } else if matches.is_present("non-empty") {
+                    Some(false)
+                } else {
+                    None
+                };
+                let columns = matches.value_of("columns").unwrap_or_default();
+                let columns = match parse_archetype_columns(columns) {
+                    Ok(columns) => columns,
+                    Err(e) => return e,
+                };
+                let min = match matches.value_of_t("min-components") {
+                    Ok(min) => min,
+                    Err(_) if !matches.is_present("min-components") => 0,
+                    Err(e) => return format!("--min-components: {}\n", e),
+                };
+                let max = match matches.value_of_t("max-components") {
+                    Ok(max) => max,
+                    Err(_) if !matches.is_present("max-components") => usize::MAX,
+                    Err(e) => return format!("--max-components: {}\n", e),
+                };
+                let desc = matches.is_present("desc");
+                list_archetypes(a, sort_by_count, desc, empty, min..=max, &columns, header_style)
+            }
             Some(("find", matches)) => {
-                if let Ok(component_id) = matches.value_of_t("componentid") {
-                    find_archetypes_by_component_id(a, component_id)
+                let only_ids = matches.is_present("only-ids");
+                if let Ok(ComponentIdArg(component_id)) = matches.value_of_t("componentid") {
+                    find_archetypes_by_component_id(a, component_id, only_ids)
                 } else if let Some(component_name) = matches.value_of("componentname") {
-                    find_archetypes_by_component_name(a, c, component_name)
+                    let (component_name, notice) = resolve_renamed(world, component_name);
+                    notice
+                        + &find_archetypes_by_component_name(
+                            a,
+                            c,
+                            component_name,
+                            raw_ids,
+                            only_ids,
+                            header_style,
+                        )
                 } else if let Ok(entity_id) = matches.value_of_t("entityid") {
                     find_archetype_by_entity_id(a, entity_id)
+                } else if matches.is_present("empty-components") {
+                    find_archetypes_without_components(a, only_ids, header_style)
                 } else {
                     // should never be hit as clap checks this
                     String::from("this line should not be hittable")
@@ -425,22 +2972,116 @@ pub fn match_commands(
             }
             Some(("info", matches)) => {
                 if let Ok(id) = matches.value_of_t("id") {
-                    print_archetype(a, c, ArchetypeId::new(id))
+                    let related = matches.is_present("related");
+                    print_archetype(a, c, ArchetypeId::new(id), NameStyle::from_matches(matches, raw_name), related)
+                } else if matches.is_present("all") {
+                    print_archetypes_table(a, header_style)
                 } else {
                     String::from("this line should not be hittable")
                 }
             }
+            Some(("similarities", matches)) => match matches.value_of_t::<f32>("threshold") {
+                Ok(threshold) if (0.0..=1.0).contains(&threshold) => {
+                    let name_style = NameStyle::from_matches(matches, raw_name);
+                    let suggest_merge = matches.is_present("suggest-merge");
+                    print_archetype_similarities(a, c, threshold, name_style, suggest_merge)
+                }
+                _ => String::from("--threshold has to be a number from 0.0 to 1.0\n"),
+            },
+            Some(("diff", matches)) => {
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                let explain = matches.is_present("explain");
+                match (matches.value_of_t("id1"), matches.value_of_t("id2")) {
+                    (Ok(id1), Ok(id2)) => print_archetype_diff(a, c, id1, id2, name_style, explain),
+                    _ => String::from("this line should not be hittable"),
+                }
+            }
             _ => String::from("this line should not be hittable"),
         },
         Some(("components", matches)) => match matches.subcommand() {
             Some(("list", matches)) => {
-                list_components(c, !matches.is_present("long"), matches.value_of("filter"))
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                let sort_by_size = matches.is_present("sort-by-size");
+                let since = if matches.is_present("new") {
+                    match world.get_resource::<EcsSnapshot>() {
+                        Some(snapshot) => snapshot.components,
+                        None => return String::from("no snapshot taken yet, run 'snapshot' first.\n"),
+                    }
+                } else {
+                    0
+                };
+                let archetypes = if matches.is_present("archetype") {
+                    match matches.values_of_t::<usize>("archetype") {
+                        Ok(ids) => match archetype_components(a, &ids) {
+                            Ok(components) => Some(components),
+                            Err(e) => return e,
+                        },
+                        Err(e) => return format!("--archetype: {}\n", e),
+                    }
+                } else {
+                    None
+                };
+                let in_use = if matches.is_present("in-use") {
+                    Some(a.iter().flat_map(|archetype| archetype.components()).collect())
+                } else {
+                    None
+                };
+                let id_range = match matches.value_of("id-range").map(parse_id_range) {
+                    Some(Ok(range)) => Some(range),
+                    Some(Err(e)) => return e,
+                    None => None,
+                };
+                let filter = ComponentFilter {
+                    contains: matches.value_of("filter"),
+                    exclude: matches.values_of("exclude").unwrap_or_default().collect(),
+                    path_prefix: matches.value_of("crate"),
+                    since,
+                    archetypes,
+                    in_use,
+                    id_range,
+                };
+                let pagination = Pagination::from_matches(matches);
+                let columns = ComponentColumns {
+                    sizes: sort_by_size,
+                    raw_bytes,
+                    raw_ids,
+                    header_style,
+                };
+                list_components(c, name_style, filter, columns, pagination)
+            }
+            Some(("find", matches)) => {
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                match matches.value_of_t("entity") {
+                    Ok(id) => {
+                        find_components_by_entity(a, c, e, id, name_style, raw_ids, header_style)
+                    }
+                    Err(e) => format!("--entity: {}\n", e),
+                }
             }
+            Some(("changed", matches)) => {
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                let entity_list = matches.is_present("entity-list");
+                let tick = match matches.value_of_t::<u32>("last-n-ticks") {
+                    Ok(n) => world.read_change_tick().saturating_sub(n),
+                    Err(_) => matches.value_of_t("since-tick").unwrap_or_default(),
+                };
+                print_changed_components(world, tick, name_style, entity_list, header_style)
+            }
+            Some(("usage", matches)) => print_component_usage(
+                a,
+                c,
+                NameStyle::from_matches(matches, raw_name),
+                matches.value_of_t("top").ok(),
+                UsageFormat::from_matches(matches, header_style),
+            ),
             Some(("info", matches)) => {
-                if let Ok(id) = matches.value_of_t("id") {
-                    print_component(c, id)
+                if let Ok(ComponentIdArg(id)) = matches.value_of_t("id") {
+                    let entities_count = matches.is_present("entities-count");
+                    print_component(world, id, raw_bytes, yes_no, raw_ids, entities_count)
                 } else if let Some(name) = matches.value_of("name") {
-                    print_component_by_name(c, name)
+                    let smart = matches.value_of("smart") == Some("true");
+                    let (name, notice) = resolve_renamed(world, name);
+                    notice + &print_component_by_name(world, name, smart, raw_bytes, yes_no, raw_ids)
                 } else {
                     String::from("this line should not be hittable")
                 }
@@ -448,12 +3089,125 @@ pub fn match_commands(
             _ => String::from("this line should not be hittable"),
         },
         Some(("entities", matches)) => match matches.subcommand() {
-            Some(("list", _)) => list_entities(e),
+            Some(("list", matches)) => {
+                let named_only = matches.is_present("with-name-only");
+                let with_name = named_only || matches.is_present("with-name");
+                let pagination = Pagination::from_matches(matches);
+                if matches.value_of("format") == Some("tree") {
+                    let unsupported = ["chunk-size", "added-since-tick", "with-name-only", "limit"];
+                    if let Some(arg) = unsupported.iter().find(|arg| matches.is_present(arg)) {
+                        return format!("--format tree can't be combined with --{}\n", arg);
+                    }
+                    return list_entities_tree(world);
+                }
+                match matches.value_of_t("added-since-tick") {
+                    Ok(tick) => list_entities_added_since(world, tick, pagination, header_style),
+                    Err(_) => list_entities(world, named_only, with_name, pagination, header_style),
+                }
+            }
+            Some(("info", matches)) => {
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                match (matches.value_of_t("id"), matches.value_of_t("diff")) {
+                    (Ok(id), Ok(diff)) => print_entity_diff(a, c, e, id, diff, name_style),
+                    (Ok(id), Err(_)) => {
+                        let component_ids = matches.is_present("component-ids");
+                        print_entity(a, c, e, id, name_style, component_ids, raw_ids)
+                    }
+                    _ => String::from("this line should not be hittable"),
+                }
+            }
             Some(("find", matches)) => {
-                if let Ok(component_id) = matches.value_of_t("componentid") {
-                    find_entities_by_component_id(a, component_id)
-                } else if let Some(component_name) = matches.value_of("componentname") {
-                    find_entities_by_component_name(a, c, component_name)
+                let exclude = component_ids(matches, "not-componentid").unwrap_or_default();
+                let count_only = matches.is_present("count-only");
+                let style = FoundEntities::from_matches(matches);
+                let pagination = Pagination::from_matches(matches).with_sample(matches);
+                let filter = FindFilter {
+                    exclude: &exclude,
+                    named_only: matches.is_present("named-only"),
+                };
+                if let Ok(component_ids) = component_ids(matches, "componentid") {
+                    find_entities_by_component_ids(world, &component_ids, filter, style, pagination)
+                } else if let Some(component_names) = matches.values_of("componentname") {
+                    let component_names: Vec<&str> = component_names.collect();
+                    let any = matches.is_present("any");
+                    let short_match = matches.is_present("short");
+                    if component_names.len() > 1 || any {
+                        return find_entities_by_component_names(
+                            world,
+                            &component_names,
+                            short_match,
+                            any,
+                            filter,
+                            style,
+                            pagination,
+                        );
+                    }
+                    let (component_name, notice) = resolve_renamed(world, component_names[0]);
+                    notice
+                        + &find_entities_by_component_name(
+                            world,
+                            component_name,
+                            short_match,
+                            filter,
+                            style,
+                            pagination,
+                        )
+                } else if let Some(tag) = matches.value_of("tag") {
+                    let (tag, notice) = resolve_renamed(world, tag);
+                    notice
+                        + &tag_size_notes(c, tag)
+                        + &find_entities_by_component_name(
+                            world, tag, false, filter,
+                            style,
+                            pagination,
+                        )
+                } else if let Some(name) = matches.value_of("name") {
+                    let case_sensitive = matches.is_present("case-sensitive");
+                    let exact = matches.is_present("exact");
+                    find_entities_by_name(
+                        world,
+                        name,
+                        case_sensitive,
+                        exact,
+                        &exclude,
+                        count_only,
+                        header_style,
+                    )
+                } else if matches.is_present("component-count-min")
+                    || matches.is_present("component-count-max")
+                {
+                    let min = matches.value_of_t("component-count-min").ok();
+                    let max = matches.value_of_t("component-count-max").ok();
+                    find_entities_by_component_count(
+                        a,
+                        min,
+                        max,
+                        &exclude,
+                        count_only,
+                        header_style,
+                    )
+                } else if let Ok(tick) = matches.value_of_t("changed-since-tick") {
+                    let name_style = NameStyle::from_matches(matches, raw_name);
+                    find_entities_by_tick(
+                        world,
+                        tick,
+                        false,
+                        name_style,
+                        &exclude,
+                        count_only,
+                        header_style,
+                    )
+                } else if let Ok(tick) = matches.value_of_t("added-since-tick") {
+                    let name_style = NameStyle::from_matches(matches, raw_name);
+                    find_entities_by_tick(
+                        world,
+                        tick,
+                        true,
+                        name_style,
+                        &exclude,
+                        count_only,
+                        header_style,
+                    )
                 } else {
                     String::from("this line should not be hittable")
                 }
@@ -461,10 +3215,265 @@ pub fn match_commands(
             _ => String::from("this line should not be hittable"),
         },
         Some(("resources", matches)) => match matches.subcommand() {
-            Some(("list", _)) => list_resources(a, c),
+            Some(("list", matches)) => {
+                let present = if matches.is_present("present") {
+                    Some(true)
+                } else if matches.is_present("absent") {
+                    Some(false)
+                } else {
+                    None
+                };
+                let send = if matches.is_present("send") {
+                    Some(true)
+                } else if matches.is_present("non-send") {
+                    Some(false)
+                } else {
+                    None
+                };
+                let filter = ResourceFilter {
+                    present,
+                    send,
+                    with_reflect: matches.is_present("with-reflect"),
+                };
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                let by_crate = matches.is_present("by-crate");
+                list_resources(world, name_style, filter, by_crate, header_style)
+            }
+            _ => String::from("this line should not be hittable"),
+        },
+        Some(("counts", matches)) => print_ecs_counts(a, c, e, matches.is_present("detailed")),
+        Some(("search", matches)) => match (matches.value_of("Term"), matches.value_of_t("min-score")) {
+            (Some(term), Ok(min_score)) => {
+                let pagination = Pagination::from_matches(matches);
+                search(world, term, min_score, raw_ids, pagination, header_style)
+            }
+            (_, Err(e)) => format!("--min-score: {}\n", e),
+            _ => String::from("this line should not be hittable"),
+        },
+        Some(("world", matches)) => match matches.subcommand() {
+            Some(("summary", _)) => {
+                let name_style = NameStyle::from_matches(matches, raw_name);
+                print_world_summary(world, name_style, raw_ids, header_style)
+            }
+            Some(("tick", _)) => print_world_tick(world),
+            Some(("inspect", _)) => inspect_world(world),
+            Some(("validate", _)) => validate_world(world),
+            Some(("export", matches)) => {
+                let ids: Option<Vec<u32>> = match matches.value_of("entities") {
+                    Some(ids) => match ids.split(',').map(|id| id.trim().parse()).collect() {
+                        Ok(ids) => Some(ids),
+                        Err(e) => return format!("--entities: {} in {}\n", e, ids),
+                    },
+                    None => None,
+                };
+                let mut exclude = Vec::new();
+                for name in matches.values_of("exclude-component").unwrap_or_default() {
+                    let ids = get_components_by_name_smart(c, name);
+                    if ids.is_empty() {
+                        return format!("no component found matching {}\n", name);
+                    }
+                    exclude.extend(ids.into_iter().map(|(id, _)| ComponentId::new(id)));
+                }
+                let path = Path::new(matches.value_of("path").unwrap_or_default());
+                export_scene(world, ids.as_deref(), &exclude, path)
+            }
             _ => String::from("this line should not be hittable"),
         },
-        Some(("counts", _)) => print_ecs_counts(a, c, e),
         _ => String::from(""),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{hierarchy::BuildWorldChildren, prelude::Component};
+
+    #[derive(Component)]
+    struct A;
+    #[derive(Component)]
+    struct B;
+    #[derive(Component)]
+    struct C;
+    #[derive(Component)]
+    #[component(storage = "SparseSet")]
+    struct S;
+
+    /// Entity 0 has A and B, 1 has A, 2 has B and C.
+    fn world() -> (World, [usize; 3]) {
+        let mut world = World::new();
+        world.spawn().insert_bundle((A, B));
+        world.spawn().insert(A);
+        world.spawn().insert_bundle((B, C));
+        let ids = [
+            world.init_component::<A>().index(),
+            world.init_component::<B>().index(),
+            world.init_component::<C>().index(),
+        ];
+        (world, ids)
+    }
+
+    fn find(world: &World, include: &[usize], exclude: &[usize], style: FoundEntities) -> String {
+        let filter = FindFilter {
+            exclude,
+            named_only: false,
+        };
+        find_entities_by_component_ids(world, include, filter, style, Pagination::default())
+    }
+
+    #[test]
+    fn find_with_include_and_exclude() {
+        let (world, [a, b, c]) = world();
+        assert_eq!(find(&world, &[a, b], &[], FoundEntities::Labels), "entities:\n0v0\n");
+        assert_eq!(find(&world, &[b], &[c], FoundEntities::Labels), "entities:\n0v0\n");
+        assert_eq!(find(&world, &[a], &[b], FoundEntities::Count), "entity count: 1\n");
+    }
+
+    #[test]
+    fn find_by_names_with_exclude() {
+        let (world, [_, _, c]) = world();
+        let filter = FindFilter {
+            exclude: &[c],
+            named_only: false,
+        };
+        let (style, pagination) = (FoundEntities::Labels, Pagination::default());
+        let names = ["A", "B"];
+        let found = |any| {
+            find_entities_by_component_names(&world, &names, true, any, filter, style, pagination)
+        };
+        assert_eq!(found(false), "entities:\n0v0\n");
+        assert_eq!(found(true), "entities:\n0v0, 1v0\n");
+    }
+
+    #[test]
+    fn find_without_matches() {
+        let (world, [a, _, c]) = world();
+        assert_eq!(find(&world, &[a, c], &[], FoundEntities::Labels), "no entites found\n");
+        assert_eq!(find(&world, &[a, c], &[], FoundEntities::Count), "entity count: 0\n");
+        assert_eq!(find(&world, &[a], &[a], FoundEntities::Ids), "");
+    }
+
+    #[test]
+    fn find_where_every_entity_matches() {
+        let (world, [a, b, c]) = world();
+        assert_eq!(find(&world, &[], &[], FoundEntities::Count), "entity count: 3\n");
+        assert_eq!(find(&world, &[], &[], FoundEntities::Ids), "0\n1\n2\n");
+
+        let mut world = world;
+        world.spawn().insert_bundle((A, B, C));
+        let all = find(&world, &[a, b, c], &[], FoundEntities::Labels);
+        assert_eq!(all, "entities:\n3v0\n");
+    }
+
+    #[test]
+    fn changed_and_added_since_tick() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(A).id();
+        let tick = world.increment_change_tick();
+        world.spawn().insert_bundle((B, S));
+        *world.get_mut::<A>(entity).unwrap() = A;
+
+        let style = HeaderStyle::Brackets;
+        assert_eq!(
+            print_changed_components(&world, tick, NameStyle::Short, true, style),
+            format!(
+                "current tick: {}\n[component name] [changed entities] [added entities]\n\
+                A 1 0\n    entities: 0v0\nB 1 1\n    entities: 1v0\nS 1 1\n    entities: 1v0\n",
+                tick + 1
+            )
+        );
+        let style = HeaderStyle::Plain;
+        let find = |added| {
+            find_entities_by_tick(&world, tick, added, NameStyle::Short, &[], false, style)
+        };
+        assert_eq!(
+            find(true),
+            format!("current tick: {}\nentity added_components\n1v0 B, S\n", tick + 1)
+        );
+        assert_eq!(
+            find(false),
+            format!("current tick: {}\nentity changed_components\n0v0 A\n1v0 B, S\n", tick + 1)
+        );
+    }
+
+    #[test]
+    fn list_entities_after_despawn() {
+        let (mut world, _) = world();
+        world.despawn(Entity::from_raw(0));
+        let style = HeaderStyle::Plain;
+        let listed = list_entities(&world, false, false, Pagination::default(), style);
+        assert_eq!(listed, "entity archetype_id\n1v0 3\n2v0 4\n2 entities\n");
+
+        start_list_operation(&mut world, 1, false, false, Pagination::default(), style);
+        let chunked = loop {
+            match continue_list_operation(&mut world) {
+                Some(ListProgress::Loading(_)) => continue,
+                Some(ListProgress::Done(output)) => break output,
+                None => panic!("the list operation is gone before it was done"),
+            }
+        };
+        assert_eq!(chunked, listed);
+    }
+
+    #[test]
+    fn entities_tree_after_despawn() {
+        let (mut world, _) = world();
+        let parent = world.spawn().insert(Name::new("parent")).id();
+        let child = world.spawn().insert(Name::new("child")).id();
+        world.entity_mut(parent).push_children(&[child]);
+        world.despawn(Entity::from_raw(0));
+        assert_eq!(list_entities_tree(&world), "1v0\n2v0\n3v0 parent\n  4v0 child\n");
+    }
+
+    #[test]
+    fn added_since_tick_after_despawn() {
+        let (mut world, _) = world();
+        world.despawn(Entity::from_raw(0));
+        let tick = world.increment_change_tick();
+        world.spawn().insert(A);
+        world.spawn().insert(A);
+        let style = HeaderStyle::Plain;
+        assert_eq!(
+            list_entities_added_since(&world, tick, Pagination::default(), style),
+            format!(
+                "current tick: {}\nentity archetype_id spawned\n0v1 3 unknown\n3v0 3 unknown\n",
+                tick + 1
+            )
+        );
+    }
+
+    #[test]
+    fn print_reserved_entity() {
+        let world = World::new();
+        let entity = world.entities().reserve_entity();
+        let output = print_entity(
+            world.archetypes(),
+            world.components(),
+            world.entities(),
+            entity.id(),
+            NameStyle::Short,
+            false,
+            false,
+        );
+        assert_eq!(
+            output,
+            "entity 0v0 exists but has no archetype/location yet, it is spawned when commands are applied\n"
+        );
+    }
+
+    #[test]
+    fn print_despawned_entity() {
+        let (mut world, _) = world();
+        let entity = Entity::from_raw(1);
+        world.despawn(entity);
+        let output = print_entity(
+            world.archetypes(),
+            world.components(),
+            world.entities(),
+            entity.id(),
+            NameStyle::Short,
+            false,
+            false,
+        );
+        assert_eq!(output, "No entity found with id: 1\n");
+    }
+}
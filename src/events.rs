@@ -0,0 +1,228 @@
+use std::{any::type_name, collections::BTreeMap, fmt::Write};
+
+use bevy::{
+    ecs::{
+        entity::{Entities, Entity},
+        event::Events,
+    },
+    prelude::{Mut, World},
+    reflect::{Reflect, ReflectMut},
+    utils::get_short_name,
+};
+use clap::{arg, App, AppSettings, ArgMatches};
+use serde_json::Value;
+
+use crate::ecs::{write_header, HeaderStyle};
+
+type FireEvent = Box<dyn Fn(&mut World, &Value) -> Result<(), String> + Send + Sync>;
+
+/// Events that `events fire` is allowed to send, keyed by their full type name.
+#[derive(Default)]
+pub struct ConsoleEvents(BTreeMap<String, FireEvent>);
+
+impl ConsoleEvents {
+    /// Allow `events fire --type T` to send `T`. The event is built from `T::default()`
+    /// with the fields given in `--data` applied through reflection.
+    pub fn register<T: Reflect + Default>(&mut self) -> &mut Self {
+        self.register_with(T::default)
+    }
+
+    /// Like [`ConsoleEvents::register`] for events without a `Default`, e.g. ones holding an
+    /// `Entity`. `template` builds the event the `--data` fields are applied to.
+    pub fn register_with<T: Reflect>(
+        &mut self,
+        template: impl Fn() -> T + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.0.insert(
+            String::from(type_name::<T>()),
+            Box::new(move |world, data| {
+                let mut event = template();
+                apply_json(&mut event, data, world.entities())?;
+                match world.get_resource_mut::<Events<T>>() {
+                    Some(mut events) => {
+                        events.send(event);
+                        Ok(())
+                    }
+                    None => Err(String::from(
+                        "the event was not added to the app with add_event",
+                    )),
+                }
+            }),
+        );
+        self
+    }
+}
+
+macro_rules! set_value {
+    ($value:expr, $convert:expr, $($ty:ty),*) => {
+        $(
+            if let Some(value) = $value.downcast_mut::<$ty>() {
+                return match $convert.and_then(|n| <$ty>::try_from(n).ok()) {
+                    Some(n) => {
+                        *value = n;
+                        Ok(())
+                    }
+                    None => Err(format!("expected a {}", type_name::<$ty>())),
+                };
+            }
+        )*
+    };
+}
+
+/// Overwrites the parts of `value` that `json` names. Objects set struct fields and arrays set
+/// tuple struct fields, the leaves have to be numbers, bools, strings or entity indices.
+fn apply_json(value: &mut dyn Reflect, json: &Value, entities: &Entities) -> Result<(), String> {
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            let fields = json.as_object().ok_or("expected an object")?;
+            for (name, field_json) in fields {
+                let field = value
+                    .field_mut(name)
+                    .ok_or_else(|| format!("no field named {}", name))?;
+                apply_json(field, field_json, entities).map_err(|e| format!("{}: {}", name, e))?;
+            }
+            Ok(())
+        }
+        ReflectMut::TupleStruct(value) => {
+            let fields = json.as_array().ok_or("expected an array")?;
+            for (index, field_json) in fields.iter().enumerate() {
+                let field = value
+                    .field_mut(index)
+                    .ok_or_else(|| format!("no field {}", index))?;
+                apply_json(field, field_json, entities).map_err(|e| format!("{}: {}", index, e))?;
+            }
+            Ok(())
+        }
+        ReflectMut::Value(value) => {
+            if let Some(entity) = value.downcast_mut::<Entity>() {
+                let id = json.as_u64().and_then(|id| u32::try_from(id).ok());
+                // use the generation of the live entity at that index when there is one
+                *entity = id
+                    .and_then(|id| entities.resolve_from_id(id))
+                    .or_else(|| id.map(Entity::from_raw))
+                    .ok_or("expected an entity index")?;
+                return Ok(());
+            }
+            if let Some(value) = value.downcast_mut::<bool>() {
+                *value = json.as_bool().ok_or("expected a bool")?;
+                return Ok(());
+            }
+            if let Some(value) = value.downcast_mut::<String>() {
+                *value = String::from(json.as_str().ok_or("expected a string")?);
+                return Ok(());
+            }
+            if let Some(value) = value.downcast_mut::<f32>() {
+                *value = json.as_f64().ok_or("expected a number")? as f32;
+                return Ok(());
+            }
+            if let Some(value) = value.downcast_mut::<f64>() {
+                *value = json.as_f64().ok_or("expected a number")?;
+                return Ok(());
+            }
+            set_value!(value, json.as_i64(), i8, i16, i32, i64, isize);
+            set_value!(value, json.as_u64(), u8, u16, u32, u64, usize);
+            Err(format!(
+                "can't set a {} from the console",
+                value.type_name()
+            ))
+        }
+        _ => Err(format!(
+            "can't set a {} from the console",
+            value.type_name()
+        )),
+    }
+}
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("events")
+            .about("send events registered in the ConsoleEvents resource")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(App::new("list").about("list the events that can be fired"))
+            .subcommand(
+                App::new("fire")
+                    .about("send an event")
+                    .args([
+                        arg!(--type <TypeName> "short or full type name of the event"),
+                        arg!(--data <Json> "fields of the event as a json object, the rest keep their default")
+                            .required(false)
+                            .multiple_values(true),
+                        arg!(--confirm <Confirm> "fired events are handled like any other, so confirm with yes")
+                            .possible_values(["yes"]),
+                    ]),
+            ),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("events", matches)) => {
+            if !world.contains_resource::<ConsoleEvents>() {
+                return String::from(
+                    "no ConsoleEvents resource found, insert one and register the events to fire.\n",
+                );
+            }
+
+            match matches.subcommand() {
+                Some(("list", matches)) => list_events(
+                    world.resource::<ConsoleEvents>(),
+                    HeaderStyle::from_matches(matches),
+                ),
+                Some(("fire", matches)) => {
+                    let name = matches.value_of("type").unwrap_or_default();
+                    // the console splits input on whitespace, put the json back together
+                    let data = matches
+                        .values_of("data")
+                        .map(|values| values.collect::<Vec<&str>>().join(" "))
+                        .unwrap_or_else(|| String::from("{}"));
+                    fire_event(world, name, data.trim_matches('\''))
+                }
+                _ => String::from("this line should not be hittable"),
+            }
+        }
+        _ => String::from(""),
+    }
+}
+
+fn list_events(events: &ConsoleEvents, header_style: HeaderStyle) -> String {
+    let mut output = String::new();
+    write_header(&mut output, header_style, &["event name"]);
+    events
+        .0
+        .keys()
+        .for_each(|name| writeln!(output, "{}", name).unwrap());
+
+    output
+}
+
+fn fire_event(world: &mut World, name: &str, data: &str) -> String {
+    let data: Value = match serde_json::from_str(data) {
+        Ok(data) => data,
+        Err(e) => return format!("--data is not valid json: {}\n", e),
+    };
+
+    world.resource_scope(|world, events: Mut<ConsoleEvents>| {
+        let matching: Vec<(&String, &FireEvent)> = events
+            .0
+            .iter()
+            .filter(|(full_name, _)| *full_name == name || get_short_name(full_name) == name)
+            .collect();
+
+        match matching.as_slice() {
+            [] => format!(
+                "No event registered with name {}, it has to derive Reflect and be registered in ConsoleEvents\n",
+                name
+            ),
+            [(full_name, fire)] => match fire(world, &data) {
+                Ok(()) => format!("fired 1 {}.\n", get_short_name(full_name)),
+                Err(e) => format!("could not fire {}: {}\n", name, e),
+            },
+            _ => format!(
+                "More than one event found with name {}, use the full type name\n",
+                name
+            ),
+        }
+    })
+}
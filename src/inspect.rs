@@ -0,0 +1,305 @@
+use std::{any::TypeId, collections::BTreeSet, fmt::Write};
+
+use bevy::{
+    ecs::{
+        archetype::{Archetype, ArchetypeId},
+        component::{ComponentId, StorageType},
+        world::World,
+    },
+    hierarchy::Parent,
+};
+
+use crate::ecs::{entity_label, type_name, NameStyle};
+
+const MAX_ARCHETYPES: usize = 1000;
+const MAX_SINGLE_ENTITY_ARCHETYPES: usize = 20;
+const MAX_EMPTY_ARCHETYPES: usize = 10;
+const MAX_ARCHETYPE_COMPONENTS: usize = 40;
+const MAX_COMPONENT_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Something `world inspect` thinks is worth a look.
+pub struct InspectIssue {
+    pub severity: Severity,
+    pub message: String,
+    /// one line on what to do about it
+    pub suggestion: String,
+}
+
+impl InspectIssue {
+    pub fn warning(message: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        InspectIssue {
+            severity: Severity::Warning,
+            message: message.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        InspectIssue {
+            severity: Severity::Error,
+            message: message.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+}
+
+/// A check run by `world inspect`, returning the issues it found.
+pub type Inspector = fn(&World) -> Vec<InspectIssue>;
+
+/// Checks `world inspect` runs on top of the built-in ones, see
+/// [`ConsoleDebugPlugin::register_inspector`].
+///
+/// [`ConsoleDebugPlugin::register_inspector`]: crate::ConsoleDebugPlugin::register_inspector
+#[derive(Default)]
+pub struct Inspectors(pub Vec<Inspector>);
+
+const BUILT_IN: [Inspector; 9] = [
+    too_many_archetypes,
+    common_sparse_set_components,
+    single_entity_archetypes,
+    empty_archetypes,
+    entities_without_components,
+    fat_archetypes,
+    large_components,
+    resources_used_as_components,
+    dangling_parents,
+];
+
+pub fn inspect_world(world: &World) -> String {
+    let custom = world
+        .get_resource::<Inspectors>()
+        .map(|inspectors| inspectors.0.clone())
+        .unwrap_or_default();
+    let check_count = BUILT_IN.len() + custom.len();
+
+    let mut issues: Vec<InspectIssue> = BUILT_IN
+        .iter()
+        .chain(custom.iter())
+        .flat_map(|inspector| inspector(world))
+        .collect();
+    // errors first
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+
+    let mut output = String::new();
+    if issues.is_empty() {
+        writeln!(output, "no issues found by {} checks.", check_count).unwrap();
+        return output;
+    }
+
+    issues.iter().for_each(|issue| {
+        let severity = match issue.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        writeln!(output, "{}: {}", severity, issue.message).unwrap();
+        writeln!(output, "    suggestion: {}", issue.suggestion).unwrap();
+    });
+    writeln!(
+        output,
+        "{} issues found by {} checks.",
+        issues.len(),
+        check_count
+    )
+    .unwrap();
+
+    output
+}
+
+/// Archetypes holding entities, without the empty and resource archetypes that always exist.
+fn entity_archetypes(world: &World) -> impl Iterator<Item = &Archetype> {
+    world
+        .archetypes()
+        .iter()
+        .filter(|archetype| archetype.id() != ArchetypeId::EMPTY)
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+}
+
+fn component_name(world: &World, id: ComponentId) -> String {
+    world
+        .components()
+        .get_info(id)
+        .map(|info| type_name(info.name(), NameStyle::Short))
+        .unwrap_or_default()
+}
+
+fn too_many_archetypes(world: &World) -> Vec<InspectIssue> {
+    let count = world.archetypes().len();
+    if count <= MAX_ARCHETYPES {
+        return Vec::new();
+    }
+
+    vec![InspectIssue::warning(
+        format!("{} archetypes, more than {}", count, MAX_ARCHETYPES),
+        "look for components added to only a few entities and use fewer distinct combinations",
+    )]
+}
+
+fn common_sparse_set_components(world: &World) -> Vec<InspectIssue> {
+    let archetypes: Vec<&Archetype> = entity_archetypes(world)
+        .filter(|archetype| !archetype.entities().is_empty())
+        .collect();
+
+    let mut issues = Vec::new();
+    for archetype in &archetypes {
+        for id in archetype.sparse_set_components() {
+            let count = archetypes
+                .iter()
+                .filter(|archetype| archetype.contains(*id))
+                .count();
+            if count * 2 > archetypes.len() {
+                issues.push(InspectIssue::warning(
+                    format!(
+                        "{} uses SparseSet storage but is in {} of {} archetypes",
+                        component_name(world, *id),
+                        count,
+                        archetypes.len()
+                    ),
+                    "components that stay on most entities iterate faster with Table storage",
+                ));
+            }
+        }
+    }
+    // every archetype with the component reports it, keep one issue per component
+    let mut seen = BTreeSet::new();
+    issues.retain(|issue| seen.insert(issue.message.clone()));
+
+    issues
+}
+
+fn single_entity_archetypes(world: &World) -> Vec<InspectIssue> {
+    let ids: Vec<String> = entity_archetypes(world)
+        .filter(|archetype| archetype.entities().len() == 1)
+        .map(|archetype| archetype.id().index().to_string())
+        .collect();
+    if ids.len() <= MAX_SINGLE_ENTITY_ARCHETYPES {
+        return Vec::new();
+    }
+
+    vec![InspectIssue::warning(
+        format!(
+            "{} archetypes hold a single entity, i.e. {}",
+            ids.len(),
+            ids[..10].join(", ")
+        ),
+        "one-off component combinations split storage, check for marker components used as state",
+    )]
+}
+
+fn empty_archetypes(world: &World) -> Vec<InspectIssue> {
+    let count = entity_archetypes(world)
+        .filter(|archetype| archetype.entities().is_empty())
+        .count();
+    if count <= MAX_EMPTY_ARCHETYPES {
+        return Vec::new();
+    }
+
+    vec![InspectIssue::warning(
+        format!("{} archetypes have no entities left", count),
+        "entities move through many component sets, add and remove components less often",
+    )]
+}
+
+fn entities_without_components(world: &World) -> Vec<InspectIssue> {
+    let count = world
+        .archetypes()
+        .get(ArchetypeId::EMPTY)
+        .map(|archetype| archetype.entities().len())
+        .unwrap_or_default();
+    if count == 0 {
+        return Vec::new();
+    }
+
+    vec![InspectIssue::warning(
+        format!("{} entities have no components", count),
+        "they may have leaked, despawn entities instead of removing all their components",
+    )]
+}
+
+fn fat_archetypes(world: &World) -> Vec<InspectIssue> {
+    entity_archetypes(world)
+        .filter(|archetype| archetype.components().count() > MAX_ARCHETYPE_COMPONENTS)
+        .map(|archetype| {
+            InspectIssue::warning(
+                format!(
+                    "archetype {} has {} components",
+                    archetype.id().index(),
+                    archetype.components().count()
+                ),
+                "split the entity up or group rarely used data into fewer components",
+            )
+        })
+        .collect()
+}
+
+fn large_components(world: &World) -> Vec<InspectIssue> {
+    let resources: BTreeSet<_> = world.archetypes().resource().components().collect();
+    let c = world.components();
+    (0..c.len())
+        .map(ComponentId::new)
+        .filter(|id| !resources.contains(id))
+        .filter_map(|id| c.get_info(id))
+        .filter(|info| info.storage_type() == StorageType::Table)
+        .filter(|info| info.layout().size() > MAX_COMPONENT_SIZE)
+        .map(|info| {
+            InspectIssue::warning(
+                format!(
+                    "{} is {} bytes",
+                    type_name(info.name(), NameStyle::Short),
+                    info.layout().size()
+                ),
+                "large components slow down iterating their table, box the data or move it to a resource",
+            )
+        })
+        .collect()
+}
+
+fn resources_used_as_components(world: &World) -> Vec<InspectIssue> {
+    // a type used as both gets two ComponentIds, so match them up by TypeId rather than by the
+    // short name, which two unrelated types can share
+    let type_id = |id| world.components().get_info(id).and_then(|info| info.type_id());
+    let components: BTreeSet<TypeId> = entity_archetypes(world)
+        .flat_map(|archetype| archetype.components())
+        .filter_map(type_id)
+        .collect();
+    let resources: BTreeSet<String> = world
+        .archetypes()
+        .resource()
+        .components()
+        .filter(|id| matches!(type_id(*id), Some(type_id) if components.contains(&type_id)))
+        .map(|id| component_name(world, id))
+        .collect();
+
+    resources
+        .iter()
+        .map(|name| {
+            InspectIssue::warning(
+                format!("{} is both a resource and a component", name),
+                "use separate types, it is easy to update one and read the other",
+            )
+        })
+        .collect()
+}
+
+fn dangling_parents(world: &World) -> Vec<InspectIssue> {
+    entity_archetypes(world)
+        .flat_map(|archetype| archetype.entities())
+        .filter_map(|entity| world.get::<Parent>(*entity).map(|parent| (*entity, **parent)))
+        .filter(|(_, parent)| world.get_entity(*parent).is_none())
+        .map(|(entity, parent)| {
+            InspectIssue::error(
+                format!(
+                    "entity {} has Parent {} which doesn't exist",
+                    entity_label(&entity),
+                    entity_label(&parent)
+                ),
+                "despawn children with despawn_recursive, or remove Parent when the parent goes",
+            )
+        })
+        .collect()
+}
@@ -1,8 +1,39 @@
 mod app;
+mod diagnostics;
 mod ecs;
+mod events;
+mod inspect;
+mod memory;
+mod network;
+#[cfg(feature = "console-puffin")]
+mod profile;
 mod reflect;
+#[cfg(feature = "console-render")]
+mod render;
+mod scene;
+mod scripts;
 mod std_io_plugin;
+mod stress;
+mod validate;
 
-pub use crate::app::{build_commands, match_commands, Pause};
-pub use crate::std_io_plugin::ConsoleDebugPlugin;
-pub use crate::ecs::{get_archetype_id_by_entity_id};
+pub use crate::app::{
+    build_commands, count_frames, expand_variables, match_commands, not_frozen, record_tick_time,
+    save_history_on_exit, split_commands, CommandSource, ConsoleHistory, ConsoleInputEvent,
+    ConsoleLock, ConsoleSession, ConsoleVars, FrameCount, KeyBindings, LockBypassSources,
+    OnOpenCommands, Pause, ResponsePrefix, StartupCommands, StepMode, TickToTimeMap, WorldFrozen,
+};
+pub use crate::diagnostics::{fps_hud_text, FpsHudHistory, FpsHudVisible};
+pub use crate::events::ConsoleEvents;
+#[cfg(feature = "console-dhat")]
+pub use crate::memory::DhatProfilerRunning;
+pub use crate::inspect::{InspectIssue, Inspector, Inspectors, Severity};
+pub use crate::network::{ChannelStats, ConnectionInfo, NetworkStats, NetworkStatsProvider};
+#[cfg(feature = "console-render")]
+pub use crate::render::{LatestRenderStats, RenderStats};
+pub use crate::scripts::ScriptsDir;
+pub use crate::std_io_plugin::{ConsoleDebugPlugin, ConsoleStage};
+pub use crate::stress::StressEntities;
+pub use crate::ecs::{
+    continue_list_operation, get_archetype_id_by_entity_id, EcsSnapshot, ListProgress,
+    PendingListOperation, TypeNameRemapper, WatchCounts,
+};
@@ -0,0 +1,106 @@
+#[cfg(feature = "console-dhat")]
+use std::fmt::Write;
+
+use bevy::prelude::World;
+use clap::{App, AppSettings, ArgMatches};
+
+/// Insert once a `dhat::Profiler::new_heap()` is running, `memory stats` refuses to read the heap
+/// stats without it since dhat panics when no profiler is running.
+#[cfg(feature = "console-dhat")]
+pub struct DhatProfilerRunning;
+
+const BYTE_UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+/// Formats a byte count for reading at a glance, i.e. `1234` -> `1.2 KB`
+pub fn fmt_bytes(n: usize) -> String {
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+
+    let mut size = n as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, BYTE_UNITS[unit])
+}
+
+/// [`fmt_bytes`], or the exact count when `--bytes` was passed
+pub fn fmt_size(n: usize, raw: bool) -> String {
+    if raw {
+        format!("{} bytes", n)
+    } else {
+        fmt_bytes(n)
+    }
+}
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("memory")
+            .about("get heap allocation info")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(App::new("stats").about("print current and peak heap usage")),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &World) -> String {
+    match matches.subcommand() {
+        Some(("memory", matches)) => match matches.subcommand() {
+            Some(("stats", _)) => print_memory_stats(world, matches.is_present("bytes")),
+            _ => String::from("this line should not be hittable"),
+        },
+        _ => String::from(""),
+    }
+}
+
+#[cfg(feature = "console-dhat")]
+fn print_memory_stats(world: &World, raw: bool) -> String {
+    // `dhat::HeapStats::get` panics when no `dhat::Profiler` is running
+    if world.get_resource::<DhatProfilerRunning>().is_none() {
+        return String::from(
+            "dhat profiler not running.\n\
+            Keep a `dhat::Profiler::new_heap()` alive while the app runs and insert the \
+            `DhatProfilerRunning` resource.\n",
+        );
+    }
+    let stats = dhat::HeapStats::get();
+
+    let mut output = String::new();
+    writeln!(
+        output,
+        "current: {} in {} allocations",
+        fmt_size(stats.curr_bytes, raw),
+        stats.curr_blocks
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "peak: {} in {} allocations",
+        fmt_size(stats.max_bytes, raw),
+        stats.max_blocks
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "total: {} in {} allocations",
+        fmt_size(stats.total_bytes as usize, raw),
+        stats.total_blocks
+    )
+    .unwrap();
+
+    output
+}
+
+#[cfg(not(feature = "console-dhat"))]
+fn print_memory_stats(_world: &World, _raw: bool) -> String {
+    String::from(
+        "memory tracking is not enabled.\n\
+        Build with the `console-dhat` feature, set `dhat::Alloc` as the `#[global_allocator]`, \
+        keep a `dhat::Profiler::new_heap()` alive while the app runs \
+        and insert the `DhatProfilerRunning` resource.\n",
+    )
+}
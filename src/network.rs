@@ -0,0 +1,135 @@
+use std::fmt::Write;
+
+use bevy::prelude::{Mut, World};
+use clap::{arg, App, AppSettings, ArgGroup, ArgMatches};
+
+use crate::ecs::{write_header, HeaderStyle};
+
+/// Traffic numbers for one channel, as reported by a [`NetworkStatsProvider`].
+pub struct ChannelStats {
+    pub name: String,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// fraction of packets lost, between 0 and 1
+    pub packet_loss: f32,
+    pub ping_ms: Option<f32>,
+}
+
+/// An open connection, as reported by a [`NetworkStatsProvider`].
+pub struct ConnectionInfo {
+    pub id: u64,
+    pub endpoint: String,
+}
+
+/// Adapter between the `network` commands and whichever networking plugin the app uses.
+pub trait NetworkStatsProvider: Send + Sync + 'static {
+    fn channel_stats(&self, world: &World) -> Vec<ChannelStats>;
+    fn connections(&self, world: &World) -> Vec<ConnectionInfo>;
+    fn disconnect(&self, world: &mut World, id: u64) -> Result<(), String>;
+}
+
+/// Insert this resource to enable the `network` commands.
+pub struct NetworkStats(pub Box<dyn NetworkStatsProvider>);
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("network")
+            .about("get network info, requires a NetworkStats resource")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(App::new("stats").about("print bandwidth and errors per channel"))
+            .subcommand(App::new("connections").about("list active connections"))
+            .subcommand(
+                App::new("disconnect")
+                    .about("close a connection")
+                    .arg(arg!(--id <Id> "id of the connection to close"))
+                    .group(ArgGroup::new("search params").args(&["id"]).required(true)),
+            ),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("network", matches)) => {
+            if !world.contains_resource::<NetworkStats>() {
+                return String::from(
+                    "no NetworkStats resource found, insert one wrapping a NetworkStatsProvider for your networking plugin.\n",
+                );
+            }
+
+            match matches.subcommand() {
+                Some(("stats", matches)) => {
+                    print_channel_stats(world, HeaderStyle::from_matches(matches))
+                }
+                Some(("connections", matches)) => {
+                    list_connections(world, HeaderStyle::from_matches(matches))
+                }
+                Some(("disconnect", matches)) => {
+                    if let Ok(id) = matches.value_of_t("id") {
+                        disconnect(world, id)
+                    } else {
+                        String::from("this line should not be hittable")
+                    }
+                }
+                _ => String::from("this line should not be hittable"),
+            }
+        }
+        _ => String::from(""),
+    }
+}
+
+fn print_channel_stats(world: &World, header_style: HeaderStyle) -> String {
+    let channels = world.resource::<NetworkStats>().0.channel_stats(world);
+
+    let mut output = String::new();
+    if channels.is_empty() {
+        writeln!(output, "the NetworkStatsProvider reports no channels").unwrap();
+        return output;
+    }
+    write_header(
+        &mut output,
+        header_style,
+        &["channel", "bytes sent", "bytes received", "packet loss", "ping"],
+    );
+    channels.iter().for_each(|channel| {
+        let ping = channel
+            .ping_ms
+            .map(|ping| format!("{:.1} ms", ping))
+            .unwrap_or_else(|| String::from("-"));
+        writeln!(
+            output,
+            "{} {} {} {:.1}% {}",
+            channel.name,
+            channel.bytes_sent,
+            channel.bytes_received,
+            channel.packet_loss * 100.0,
+            ping
+        )
+        .unwrap()
+    });
+
+    output
+}
+
+fn list_connections(world: &World, header_style: HeaderStyle) -> String {
+    let connections = world.resource::<NetworkStats>().0.connections(world);
+
+    let mut output = String::new();
+    write_header(&mut output, header_style, &["connection id", "endpoint"]);
+    connections.iter().for_each(|connection| {
+        writeln!(output, "{} {}", connection.id, connection.endpoint).unwrap()
+    });
+
+    output
+}
+
+fn disconnect(world: &mut World, id: u64) -> String {
+    let result =
+        world.resource_scope(|world, stats: Mut<NetworkStats>| stats.0.disconnect(world, id));
+
+    match result {
+        Ok(()) => format!("disconnected connection {}\n", id),
+        Err(error) => format!("could not disconnect connection {}: {}\n", id, error),
+    }
+}
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, fmt::Write};
+
+use bevy::prelude::World;
+use clap::{arg, App, AppSettings, ArgMatches};
+use puffin::{
+    GlobalFrameView, GlobalProfiler, Reader, ScopeCollection, ScopeId, Stream, ThreadProfiler,
+};
+
+/// Scopes opened with `profile start`, innermost last, along with the
+/// stream offset puffin needs to close them.
+#[derive(Default)]
+pub struct ProfileScopes {
+    open: Vec<(String, usize)>,
+    ids: HashMap<String, ScopeId>,
+}
+
+/// Keeps the frames puffin reports so `profile report` has something to print.
+#[derive(Default)]
+pub struct ProfileFrames(pub GlobalFrameView);
+
+/// puffin only collects scopes into frames when told a new frame started
+pub fn new_frame() {
+    GlobalProfiler::lock().new_frame();
+}
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("profile")
+            .about("control puffin profiling scopes")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                App::new("start")
+                    .about("begin a profiling scope")
+                    .arg(arg!(<label> "name of the scope")),
+            )
+            .subcommand(
+                App::new("stop")
+                    .about("end a profiling scope")
+                    .arg(arg!(<label> "name of the scope")),
+            )
+            .subcommand(App::new("report").about("print the scopes of the last profiled frame"))
+            .subcommand(App::new("clear").about("clear recorded profiling data")),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("profile", matches)) => match matches.subcommand() {
+            Some(("start", matches)) => start_scope(
+                &mut world.resource_mut::<ProfileScopes>(),
+                matches.value_of("label").unwrap_or_default(),
+            ),
+            Some(("stop", matches)) => stop_scope(
+                &mut world.resource_mut::<ProfileScopes>(),
+                matches.value_of("label").unwrap_or_default(),
+            ),
+            Some(("report", _)) => print_report(world.resource::<ProfileFrames>()),
+            Some(("clear", _)) => {
+                world.insert_resource(ProfileFrames::default());
+                String::from("cleared profiling data.\n")
+            }
+            _ => String::from("this line should not be hittable"),
+        },
+        _ => String::from(""),
+    }
+}
+
+fn start_scope(scopes: &mut ProfileScopes, label: &str) -> String {
+    if scopes.open.iter().any(|(name, _)| name == label) {
+        return format!("scope {} is already started\n", label);
+    }
+
+    let scope_id = scopes.ids.get(label).copied();
+    let (scope_id, offset) = ThreadProfiler::call(|tp| {
+        let scope_id = scope_id.unwrap_or_else(|| {
+            tp.register_named_scope(label.to_owned(), "console", file!(), line!())
+        });
+        (scope_id, tp.begin_scope(scope_id, ""))
+    });
+    scopes.ids.insert(String::from(label), scope_id);
+    scopes.open.push((String::from(label), offset));
+
+    format!("started scope {}\n", label)
+}
+
+fn stop_scope(scopes: &mut ProfileScopes, label: &str) -> String {
+    match scopes.open.last() {
+        Some((name, offset)) if name == label => {
+            let offset = *offset;
+            ThreadProfiler::call(|tp| tp.end_scope(offset));
+            scopes.open.pop();
+            format!("stopped scope {}\n", label)
+        }
+        // puffin scopes have to be closed innermost first
+        Some((name, _)) if scopes.open.iter().any(|(open, _)| open == label) => {
+            format!("scope {} has to be stopped before {}\n", name, label)
+        }
+        _ => format!("no started scope named {}\n", label),
+    }
+}
+
+fn print_report(frames: &ProfileFrames) -> String {
+    let view = frames.0.lock();
+    let frame = match view.latest_frame() {
+        Some(frame) => frame,
+        None => return String::from("no profiling data recorded\n"),
+    };
+
+    let mut output = String::new();
+    writeln!(
+        output,
+        "frame {} ({:.3} ms)",
+        frame.frame_index(),
+        frame.duration_ns() as f64 / 1e6
+    )
+    .unwrap();
+    frame
+        .unpacked()
+        .iter()
+        .flat_map(|unpacked| unpacked.thread_streams.iter())
+        .for_each(|(thread, stream_info)| {
+            writeln!(output, "thread {}", thread.name).unwrap();
+            write_scopes(
+                &mut output,
+                view.scope_collection(),
+                &stream_info.stream,
+                0,
+                1,
+            );
+        });
+
+    output
+}
+
+fn write_scopes(
+    output: &mut String,
+    scopes: &ScopeCollection,
+    stream: &Stream,
+    offset: u64,
+    depth: usize,
+) {
+    let reader = match Reader::with_offset(stream, offset) {
+        Ok(reader) => reader,
+        Err(_) => return,
+    };
+
+    for scope in reader.flatten() {
+        let name = scopes
+            .fetch_by_id(&scope.id)
+            .map(|details| details.name().to_string())
+            .unwrap_or_else(|| String::from("<unknown>"));
+        writeln!(
+            output,
+            "{:indent$}{} {:.3} ms",
+            "",
+            name,
+            scope.record.duration_ns as f64 / 1e6,
+            indent = depth * 2
+        )
+        .unwrap();
+        write_scopes(
+            output,
+            scopes,
+            stream,
+            scope.child_begin_position,
+            depth + 1,
+        );
+    }
+}
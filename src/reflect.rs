@@ -17,20 +17,24 @@ pub fn build_commands(app: App) -> App {
 pub fn match_commands(matches: &ArgMatches, reflect: &TypeRegistry) -> String {
     match matches.subcommand() {
         Some(("reflect", matches)) => match matches.subcommand() {
-            Some(("list", _)) => list_reflection(reflect),
+            Some(("list", _)) => list_reflection(reflect, !matches.is_present("raw-name")),
             _ => String::from("this line should not be able to be run"),
         },
         _ => String::from(""),
     }
 }
 
-fn list_reflection(reflect: &TypeRegistry) -> String {
+fn list_reflection(reflect: &TypeRegistry, short: bool) -> String {
     let mut output = String::new();
 
     let type_registry = reflect.read();
 
     type_registry.iter().for_each(|type_registration| {
-        writeln!(output, "{}", type_registration.short_name()).unwrap()
+        if short {
+            writeln!(output, "{}", type_registration.short_name()).unwrap()
+        } else {
+            writeln!(output, "{}", type_registration.type_name()).unwrap()
+        }
     });
 
     output
@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use bevy::{
+    core_pipeline::{
+        core_2d::Transparent2d,
+        core_3d::{AlphaMask3d, Opaque3d, Transparent3d},
+    },
+    prelude::{App, Query, Res, ResMut, World},
+    render::{
+        mesh::{GpuBufferInfo, Mesh},
+        render_asset::RenderAssets,
+        render_phase::{PhaseItem, RenderPhase},
+        texture::Image,
+        RenderApp, RenderStage,
+    },
+};
+use clap::{arg, App as ClapApp, AppSettings, ArgMatches};
+use crossbeam::channel::{bounded, Receiver, Sender};
+
+use crate::ecs::{write_header, HeaderStyle};
+use crate::memory::fmt_size;
+use crate::std_io_plugin::ConsoleStage;
+
+/// Numbers captured in the render world for the last rendered frame.
+#[derive(Default)]
+pub struct RenderStats {
+    /// (pass name, number of items queued to draw in it)
+    pub passes: Vec<(&'static str, usize)>,
+    pub meshes: usize,
+    /// indices of indexed meshes plus vertices of the others
+    pub elements: u64,
+    pub textures: usize,
+    pub texture_bytes: u64,
+}
+
+/// Most recent [`RenderStats`] forwarded from the render world.
+#[derive(Default)]
+pub struct LatestRenderStats(pub Option<RenderStats>);
+
+struct RenderStatsSender(Sender<RenderStats>);
+struct RenderStatsReceiver(Receiver<RenderStats>);
+
+pub fn setup(app: &mut App) {
+    let (tx, rx) = bounded(1);
+
+    if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app
+            .insert_resource(RenderStatsSender(tx))
+            .add_system_to_stage(RenderStage::Cleanup, send_render_stats);
+    }
+
+    app.init_resource::<LatestRenderStats>()
+        .insert_resource(RenderStatsReceiver(rx))
+        .add_system_to_stage(ConsoleStage, receive_render_stats);
+}
+
+fn phase_len<I: PhaseItem>(phases: &Query<&RenderPhase<I>>) -> usize {
+    phases.iter().map(|phase| phase.items.len()).sum()
+}
+
+fn send_render_stats(
+    sender: Res<RenderStatsSender>,
+    meshes: Option<Res<RenderAssets<Mesh>>>,
+    images: Option<Res<RenderAssets<Image>>>,
+    opaque_3d: Query<&RenderPhase<Opaque3d>>,
+    alpha_mask_3d: Query<&RenderPhase<AlphaMask3d>>,
+    transparent_3d: Query<&RenderPhase<Transparent3d>>,
+    transparent_2d: Query<&RenderPhase<Transparent2d>>,
+) {
+    let mut stats = RenderStats {
+        passes: vec![
+            ("opaque_3d", phase_len(&opaque_3d)),
+            ("alpha_mask_3d", phase_len(&alpha_mask_3d)),
+            ("transparent_3d", phase_len(&transparent_3d)),
+            ("transparent_2d", phase_len(&transparent_2d)),
+        ],
+        ..Default::default()
+    };
+
+    if let Some(meshes) = meshes {
+        stats.meshes = meshes.len();
+        stats.elements = meshes
+            .values()
+            .map(|mesh| match &mesh.buffer_info {
+                GpuBufferInfo::Indexed { count, .. } => *count as u64,
+                GpuBufferInfo::NonIndexed { vertex_count } => *vertex_count as u64,
+            })
+            .sum();
+    }
+
+    if let Some(images) = images {
+        stats.textures = images.len();
+        // only the top mip level of a single layer, so this is a lower bound
+        stats.texture_bytes = images
+            .values()
+            .map(|image| {
+                let info = image.texture_format.describe();
+                let (block_width, block_height) = info.block_dimensions;
+                (image.size.x as u64 / block_width as u64)
+                    * (image.size.y as u64 / block_height as u64)
+                    * info.block_size as u64
+            })
+            .sum();
+    }
+
+    // the main world only needs the latest frame, drop stats it hasn't picked up yet
+    let _ = sender.0.try_send(stats);
+}
+
+fn receive_render_stats(receiver: Res<RenderStatsReceiver>, mut latest: ResMut<LatestRenderStats>) {
+    if let Some(stats) = receiver.0.try_iter().last() {
+        latest.0 = Some(stats);
+    }
+}
+
+pub fn build_commands(app: ClapApp) -> ClapApp {
+    let app = app.subcommand(
+        ClapApp::new("render")
+            .about("get renderer info")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                ClapApp::new("info")
+                    .about("print draw, mesh and texture counts for the last frame")
+                    .arg(
+                        arg!(--format [Format] "table also prints the items drawn per pass")
+                            .possible_values(["summary", "table"])
+                            .default_value("summary"),
+                    ),
+            ),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("render", matches)) => match matches.subcommand() {
+            Some(("info", matches)) => print_render_info(
+                world.get_resource::<LatestRenderStats>(),
+                matches.value_of("format") == Some("table"),
+                matches.is_present("bytes"),
+                HeaderStyle::from_matches(matches),
+            ),
+            _ => String::from("this line should not be hittable"),
+        },
+        _ => String::from(""),
+    }
+}
+
+fn print_render_info(
+    latest: Option<&LatestRenderStats>,
+    table: bool,
+    raw_bytes: bool,
+    header_style: HeaderStyle,
+) -> String {
+    let stats = match latest.and_then(|latest| latest.0.as_ref()) {
+        Some(stats) => stats,
+        None => return String::from("no render stats received yet\n"),
+    };
+
+    let mut output = String::new();
+    let draws: usize = stats.passes.iter().map(|(_, count)| count).sum();
+    writeln!(output, "draw calls: {}", draws).unwrap();
+    if table {
+        write_header(&mut output, header_style, &["pass", "draw calls"]);
+        stats
+            .passes
+            .iter()
+            .for_each(|(pass, count)| writeln!(output, "{} {}", pass, count).unwrap());
+    }
+    writeln!(
+        output,
+        "meshes: {} ({} indices/vertices)",
+        stats.meshes, stats.elements
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "textures: {} (at least {})",
+        stats.textures,
+        fmt_size(stats.texture_bytes as usize, raw_bytes)
+    )
+    .unwrap();
+
+    output
+}
@@ -0,0 +1,337 @@
+use std::path::Path;
+
+use bevy::{ecs::component::ComponentId, prelude::World};
+#[cfg(feature = "console-scene")]
+use bevy::{
+    ecs::{
+        archetype::ArchetypeId,
+        entity::{Entity, EntityMap},
+        reflect::ReflectComponent,
+    },
+    hierarchy::{Children, Parent},
+    reflect::{TypeRegistry, TypeRegistryInternal},
+    scene::{serde::SceneDeserializer, DynamicEntity, DynamicScene},
+};
+#[cfg(feature = "console-scene")]
+use serde::de::DeserializeSeed;
+#[cfg(feature = "console-scene")]
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+};
+
+#[cfg(feature = "console-scene")]
+use crate::ecs::entity_label;
+
+/// Spawns the entities of the `.scn.ron` scene at `path` into `world`. With `dry_run` it only
+/// checks the file parses and every component in it can be spawned.
+#[cfg(feature = "console-scene")]
+pub fn import_scene(world: &mut World, path: &Path, dry_run: bool) -> String {
+    let scene = match load_scene(world, path) {
+        Ok(scene) => scene,
+        Err(e) => return e,
+    };
+    if let Err(e) = check_components(world, &scene) {
+        return e;
+    }
+
+    let components: usize = scene
+        .entities
+        .iter()
+        .map(|entity| entity.components.len())
+        .sum();
+    if dry_run {
+        return format!(
+            "{} is valid: {} entities with {} components, nothing spawned.\n",
+            path.display(),
+            scene.entities.len(),
+            components
+        );
+    }
+
+    let mut entity_map = EntityMap::default();
+    if let Err(e) = scene.write_to_world(world, &mut entity_map) {
+        return format!("could not spawn {}: {}\n", path.display(), e);
+    }
+    let mut spawned: Vec<_> = entity_map.values().collect();
+    spawned.sort_by_key(|entity| entity.id());
+    let labels: Vec<String> = spawned.iter().map(entity_label).collect();
+
+    format!(
+        "spawned {} entities from {}:\n{}\n",
+        labels.len(),
+        path.display(),
+        labels.join(", ")
+    )
+}
+
+#[cfg(feature = "console-scene")]
+fn load_scene(world: &World, path: &Path) -> Result<DynamicScene, String> {
+    let file = fs::read(path)
+        .map_err(|e| format!("could not read scene {}: {}\n", path.display(), e))?;
+    let registry = world
+        .get_resource::<TypeRegistry>()
+        .ok_or_else(|| String::from("scenes are read through reflection, add a TypeRegistry.\n"))?;
+    let mut deserializer = ron::de::Deserializer::from_bytes(&file)
+        .map_err(|e| format!("could not parse scene {}: {}\n", path.display(), e))?;
+    let scene_deserializer = SceneDeserializer {
+        type_registry: &registry.read(),
+    };
+
+    scene_deserializer
+        .deserialize(&mut deserializer)
+        .map_err(|e| format!("could not parse scene {}: {}\n", path.display(), e))
+}
+
+/// Spawning stops at the first component that isn't registered with `#[reflect(Component)]`,
+/// so they are all looked up before anything is spawned.
+#[cfg(feature = "console-scene")]
+fn check_components(world: &World, scene: &DynamicScene) -> Result<(), String> {
+    let registry = world.resource::<TypeRegistry>().read();
+    let missing: BTreeSet<&str> = scene
+        .entities
+        .iter()
+        .flat_map(|entity| entity.components.iter())
+        .map(|component| component.type_name())
+        .filter(|name| {
+            !matches!(
+                registry.get_with_name(name),
+                Some(registration) if registration.data::<ReflectComponent>().is_some()
+            )
+        })
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let missing: Vec<&str> = missing.into_iter().collect();
+    Err(format!(
+        "these components need #[reflect(Component)] and to be registered to spawn: {}\n",
+        missing.join(", ")
+    ))
+}
+
+/// Writes the entities with an index in `ids`, or all of them when it is `None`, to `path` as a
+/// `.scn.ron` scene that `world import` and bevy's scene loader read back. Components in
+/// `exclude` and the ones not registered with `#[reflect(Component)]` are left out.
+#[cfg(feature = "console-scene")]
+pub fn export_scene(
+    world: &World,
+    ids: Option<&[u32]>,
+    exclude: &[ComponentId],
+    path: &Path,
+) -> String {
+    let registry = match world.get_resource::<TypeRegistry>() {
+        Some(registry) => registry,
+        None => {
+            return String::from("scenes are written through reflection, add a TypeRegistry.\n")
+        }
+    };
+    let selected: Option<HashSet<Entity>> = match ids {
+        Some(ids) => {
+            let mut selected = HashSet::new();
+            for id in ids {
+                // freed ids still resolve, to the generation their next entity gets
+                let entity = world.entities().resolve_from_id(*id);
+                match entity.filter(|entity| world.get_entity(*entity).is_some()) {
+                    Some(entity) => selected.insert(entity),
+                    None => return format!("No entity found with id: {}\n", id),
+                };
+            }
+            Some(selected)
+        }
+        None => None,
+    };
+
+    let export = scene_from_world(world, &registry.read(), selected.as_ref(), exclude);
+    let ron = match export.scene.serialize_ron(registry) {
+        Ok(ron) => ron,
+        Err(e) => return format!("could not serialize the scene: {}\n", e),
+    };
+    if let Err(e) = fs::write(path, ron) {
+        return format!("could not write to {}: {}\n", path.display(), e);
+    }
+
+    let components: usize = export
+        .scene
+        .entities
+        .iter()
+        .map(|entity| entity.components.len())
+        .sum();
+    let mut output = format!(
+        "exported {} entities with {} components to {}.\n",
+        export.scene.entities.len(),
+        components,
+        path.display()
+    );
+    if !export.not_reflected.is_empty() {
+        let names: Vec<&str> = export.not_reflected.into_iter().collect();
+        output.push_str(&format!(
+            "left out components without #[reflect(Component)]: {}\n",
+            names.join(", ")
+        ));
+    }
+    if export.cut_hierarchy > 0 {
+        output.push_str(&format!(
+            "left out Parent and Children of {} entities related to entities not exported.\n",
+            export.cut_hierarchy
+        ));
+    }
+
+    output
+}
+
+#[cfg(feature = "console-scene")]
+struct SceneExport<'a> {
+    scene: DynamicScene,
+    /// names of the components that couldn't be exported
+    not_reflected: BTreeSet<&'a str>,
+    /// entities whose `Parent` or `Children` were dropped
+    cut_hierarchy: usize,
+}
+
+/// Like `DynamicScene::from_world`, but only for the `selected` entities and leaving out the
+/// `exclude` components. Importing a `Parent` or `Children` pointing at an entity that isn't in
+/// the scene panics, so those are dropped.
+#[cfg(feature = "console-scene")]
+fn scene_from_world<'a>(
+    world: &'a World,
+    registry: &TypeRegistryInternal,
+    selected: Option<&HashSet<Entity>>,
+    exclude: &[ComponentId],
+) -> SceneExport<'a> {
+    let is_selected = |entity: &Entity| match selected {
+        Some(selected) => selected.contains(entity),
+        None => true,
+    };
+    let outside_hierarchy = |entity: Entity| {
+        let parent = world.get::<Parent>(entity).map(|parent| **parent);
+        let children = world.get::<Children>(entity).map(|children| children.iter());
+        parent
+            .into_iter()
+            .chain(children.into_iter().flatten().copied())
+            .any(|related| !is_selected(&related))
+    };
+    let hierarchy = [
+        world.components().get_id(std::any::TypeId::of::<Parent>()),
+        world.components().get_id(std::any::TypeId::of::<Children>()),
+    ];
+
+    let mut export = SceneExport {
+        scene: DynamicScene::default(),
+        not_reflected: BTreeSet::new(),
+        cut_hierarchy: 0,
+    };
+    for archetype in world.archetypes().iter() {
+        if archetype.id() == ArchetypeId::RESOURCE {
+            continue;
+        }
+        for entity in archetype.entities().iter().filter(|entity| is_selected(entity)) {
+            let cut = outside_hierarchy(*entity);
+            export.cut_hierarchy += usize::from(cut);
+            let mut components = Vec::new();
+            for id in archetype.components() {
+                if exclude.contains(&id) || (cut && hierarchy.contains(&Some(id))) {
+                    continue;
+                }
+                let info = world.components().get_info(id).unwrap();
+                let reflect = info
+                    .type_id()
+                    .and_then(|type_id| registry.get(type_id))
+                    .and_then(|registration| registration.data::<ReflectComponent>());
+                match reflect.and_then(|reflect| reflect.reflect(world, *entity)) {
+                    Some(component) => components.push(component.clone_value()),
+                    None => {
+                        export.not_reflected.insert(info.name());
+                    }
+                }
+            }
+            export.scene.entities.push(DynamicEntity {
+                entity: entity.id(),
+                components,
+            });
+        }
+    }
+    export.scene.entities.sort_by_key(|entity| entity.entity);
+
+    export
+}
+
+#[cfg(not(feature = "console-scene"))]
+pub fn export_scene(
+    _world: &World,
+    _ids: Option<&[u32]>,
+    _exclude: &[ComponentId],
+    _path: &Path,
+) -> String {
+    not_enabled()
+}
+
+#[cfg(not(feature = "console-scene"))]
+pub fn import_scene(_world: &mut World, _path: &Path, _dry_run: bool) -> String {
+    not_enabled()
+}
+
+#[cfg(not(feature = "console-scene"))]
+fn not_enabled() -> String {
+    String::from(
+        "scenes are not enabled.\n\
+        Build with the `console-scene` feature to import and export .scn.ron files.\n",
+    )
+}
+
+#[cfg(all(test, feature = "console-scene"))]
+mod tests {
+    use super::*;
+    use bevy::prelude::{Component, Reflect};
+
+    #[derive(Component, Default, Reflect)]
+    #[reflect(Component)]
+    struct Score(u32);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<TypeRegistry>();
+        world.resource::<TypeRegistry>().write().register::<Score>();
+        world
+    }
+
+    #[test]
+    fn export_then_import() {
+        let path = std::env::temp_dir().join("bevy_mod_debug_console_round_trip.scn.ron");
+        let mut exported = world();
+        exported.spawn().insert(Score(3));
+        exported.spawn().insert(Score(5));
+        assert_eq!(
+            export_scene(&exported, None, &[], &path),
+            format!("exported 2 entities with 2 components to {}.\n", path.display())
+        );
+
+        let mut imported = world();
+        assert_eq!(
+            import_scene(&mut imported, &path, false),
+            format!("spawned 2 entities from {}:\n0v0, 1v0\n", path.display())
+        );
+        let mut scores: Vec<u32> = imported
+            .query::<&Score>()
+            .iter(&imported)
+            .map(|score| score.0)
+            .collect();
+        scores.sort_unstable();
+        assert_eq!(scores, [3, 5]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn export_despawned_entity() {
+        let path = std::env::temp_dir().join("bevy_mod_debug_console_despawned.scn.ron");
+        let mut world = world();
+        let entity = world.spawn().insert(Score(3)).id();
+        world.despawn(entity);
+        assert_eq!(
+            export_scene(&world, Some(&[entity.id()]), &[], &path),
+            "No entity found with id: 0\n"
+        );
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{ecs::event::Events, prelude::World};
+use clap::{arg, App, AppSettings, ArgMatches};
+
+use crate::app::{CommandSource, ConsoleInputEvent};
+
+/// Files with these extensions in the scripts directory are scripts.
+const SCRIPT_EXTENSIONS: [&str; 2] = ["console", "txt"];
+
+/// The directory the `scripts` commands look in, see [`ConsoleDebugPlugin::with_scripts_dir`],
+/// and the script files found there by the last scan.
+///
+/// [`ConsoleDebugPlugin::with_scripts_dir`]: crate::ConsoleDebugPlugin::with_scripts_dir
+#[derive(Default)]
+pub struct ScriptsDir {
+    pub dir: Option<PathBuf>,
+    /// file names, sorted
+    pub scripts: Vec<String>,
+}
+
+impl ScriptsDir {
+    /// Scans `dir` right away, a missing directory just has no scripts yet.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        let scripts = match &dir {
+            Some(dir) => scan_scripts(dir).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        ScriptsDir { dir, scripts }
+    }
+}
+
+/// The names of the script files in `dir`.
+fn scan_scripts(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("could not read scripts from {}: {}\n", dir.display(), e))?;
+    let mut scripts: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|extension| extension.to_str()),
+                Some(extension) if SCRIPT_EXTENSIONS.contains(&extension)
+            )
+        })
+        .filter_map(|path| Some(path.file_name()?.to_str()?.to_owned()))
+        .collect();
+    scripts.sort();
+
+    Ok(scripts)
+}
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("scripts")
+            .about("run files of console commands from the scripts directory")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                App::new("list").about("list the .console and .txt files in the scripts directory"),
+            )
+            .subcommand(
+                App::new("run")
+                    .about("run the commands in a script, one per line, skipping # and // comments")
+                    .args([
+                        arg!(<Name> "the script's name, with or without extension, or a path"),
+                        arg!(--"echo-comments" "print the script's comments between the command outputs"),
+                    ]),
+            )
+            .subcommand(
+                App::new("reload").about("scan the scripts directory again for added or removed scripts"),
+            ),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("scripts", matches)) => {
+            let mut scripts = world.get_resource_or_insert_with(ScriptsDir::default);
+            match matches.subcommand() {
+                Some(("list", _)) => list_scripts(&scripts),
+                Some(("run", matches)) => {
+                    let name = matches.value_of("Name").unwrap_or_default();
+                    let path = match find_script(&scripts, name) {
+                        Ok(path) => path,
+                        Err(e) => return e,
+                    };
+                    run_script(world, &path, matches.is_present("echo-comments"))
+                }
+                Some(("reload", _)) => reload_scripts(&mut scripts),
+                _ => String::from("this line should not be hittable"),
+            }
+        }
+        _ => String::from(""),
+    }
+}
+
+fn list_scripts(scripts: &ScriptsDir) -> String {
+    let dir = match &scripts.dir {
+        Some(dir) => dir,
+        None => return no_scripts_dir(),
+    };
+
+    let mut output = String::new();
+    if scripts.scripts.is_empty() {
+        writeln!(output, "no scripts found in {}", dir.display()).unwrap();
+        return output;
+    }
+    writeln!(output, "[script] in {}", dir.display()).unwrap();
+    scripts
+        .scripts
+        .iter()
+        .for_each(|script| writeln!(output, "{}", script).unwrap());
+
+    output
+}
+
+fn reload_scripts(scripts: &mut ScriptsDir) -> String {
+    let dir = match &scripts.dir {
+        Some(dir) => dir,
+        None => return no_scripts_dir(),
+    };
+
+    match scan_scripts(dir) {
+        Ok(found) => {
+            let output = format!("found {} scripts in {}\n", found.len(), dir.display());
+            scripts.scripts = found;
+            output
+        }
+        Err(e) => e,
+    }
+}
+
+/// The script in the scripts directory called `name`, with or without its extension. Any other
+/// existing file is run as a path.
+fn find_script(scripts: &ScriptsDir, name: &str) -> Result<PathBuf, String> {
+    let found = scripts.scripts.iter().find(|script| {
+        *script == name
+            || matches!(Path::new(script).file_stem(), Some(stem) if stem == name)
+    });
+    match (&scripts.dir, found) {
+        (Some(dir), Some(script)) => Ok(dir.join(script)),
+        _ if Path::new(name).is_file() => Ok(PathBuf::from(name)),
+        (Some(_), None) => Err(format!(
+            "no script named {}, run 'scripts list' to see them or 'scripts reload' if it was just added\n",
+            name
+        )),
+        (None, _) => Err(no_scripts_dir()),
+    }
+}
+
+/// Queues the commands in the script at `path` as [`ConsoleInputEvent`]s, they run next frame.
+/// With `echo_comments` the comments are queued too, in order, and print without running.
+fn run_script(world: &mut World, path: &Path, echo_comments: bool) -> String {
+    let file = match fs::read_to_string(path) {
+        Ok(file) => file,
+        Err(e) => return format!("could not read script {}: {}\n", path.display(), e),
+    };
+    let mut events = match world.get_resource_mut::<Events<ConsoleInputEvent>>() {
+        Some(events) => events,
+        None => {
+            return String::from(
+                "scripts run through ConsoleInputEvents, add the ConsoleDebugPlugin to run them.\n",
+            )
+        }
+    };
+
+    let mut send = |line: &str| {
+        events.send(ConsoleInputEvent {
+            command: String::from(line),
+            source: CommandSource::Script,
+        })
+    };
+    let mut count = 0;
+    for line in file.lines() {
+        let (command, comment) = split_comment(line);
+        if let (Some(comment), true) = (comment, echo_comments) {
+            send(comment);
+        }
+        if !command.is_empty() {
+            send(command);
+            count += 1;
+        }
+    }
+
+    format!("running {} commands from {}\n", count, path.display())
+}
+
+/// Whether `line` is only a comment, starting with `#` or `//`. The console prints them
+/// without running anything.
+pub(crate) fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') || line.starts_with("//")
+}
+
+/// The command on a script line and its comment, i.e. `entities list // show all entities`
+/// splits into `entities list` and `// show all entities`. A `//` only starts a comment at the
+/// start of the line or after whitespace, so paths like `a//b` are kept.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let line = line.trim();
+    if is_comment(line) {
+        return ("", Some(line));
+    }
+    let start = line
+        .match_indices("//")
+        .map(|(start, _)| start)
+        .find(|start| line[..*start].ends_with(char::is_whitespace));
+    match start {
+        Some(start) => (line[..start].trim_end(), Some(&line[start..])),
+        None => (line, None),
+    }
+}
+
+fn no_scripts_dir() -> String {
+    String::from(
+        "no scripts directory set, use ConsoleDebugPlugin::with_scripts_dir to set one.\n",
+    )
+}
@@ -1,40 +1,133 @@
-use crate::app::{build_commands, input_pause, match_commands, pause, EnteringConsole, Pause};
-use bevy::{
-    ecs::{archetype::Archetypes, component::Components, entity::Entities},
-    prelude::*,
-    reflect::TypeRegistry,
-    tasks::AsyncComputeTaskPool,
+use crate::app::{
+    build_commands, count_frames, expand_variables, input_pause, match_commands,
+    match_commands_unlocked, not_frozen, pause, record_tick_time, run_key_bindings, save_history_on_exit,
+    split_commands, take_step, CommandSource, ConsoleHistory, ConsoleInputEvent, ConsoleLock,
+    ConsoleSession, EnteringConsole, FrameCount, KeyBindings, LockBypassSources, OnOpenCommands,
+    Pause, ResponsePrefix, StartupCommands, StepMode, TickToTimeMap, WorldFrozen,
 };
+use crate::diagnostics::{print_fps_hud, FpsHudHistory, FpsHudVisible};
+use crate::ecs::{continue_list_operation, print_watched_counts, ListProgress, WatchCounts};
+use crate::events::ConsoleEvents;
+use crate::inspect::{Inspector, Inspectors};
+#[cfg(feature = "console-puffin")]
+use crate::profile::{self, ProfileFrames, ProfileScopes};
+#[cfg(feature = "console-render")]
+use crate::render;
+use crate::scripts::{is_comment, ScriptsDir};
+use bevy::{ecs::event::Events, prelude::*, tasks::AsyncComputeTaskPool};
 use crossbeam::channel::{bounded, Receiver};
-use std::io::{self, BufRead, Write};
-
-fn parse_input(
-    a: &Archetypes,
-    c: &Components,
-    e: &Entities,
-    reflect: Res<TypeRegistry>,
-    mut pause: ResMut<Pause>,
-    line_channel: Res<Receiver<String>>,
-) {
-    if let Ok(line) = line_channel.try_recv() {
-        let app_name = "";
-        println!();
-        let split = line.split_whitespace();
-        let mut args = vec![app_name];
-        args.append(&mut split.collect());
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    time::SystemTime,
+};
 
-        let matches_result = build_commands(app_name).try_get_matches_from(args);
+/// Parses and runs one line of console input, returning what it printed or why it failed.
+/// `check_lock` is false for sources allowed to bypass the console lock.
+fn run_command(line: &str, world: &mut World, check_lock: bool) -> String {
+    let app_name = "";
+    let split: Vec<&str> = line.split_whitespace().collect();
+    let mut args = vec![String::from(app_name)];
+    match expand_variables(world, &split) {
+        Ok(mut expanded) => args.append(&mut expanded),
+        Err(e) => return e,
+    }
 
-        if let Err(e) = matches_result {
-            println!("{}", e);
-            print!(">>> ");
+    let output = match build_commands(app_name).try_get_matches_from(args) {
+        Ok(matches) if check_lock => match_commands(&matches, world),
+        Ok(matches) => match_commands_unlocked(&matches, world),
+        Err(e) => e.to_string(),
+    };
+    if let Some(mut session) = world.get_resource_mut::<ConsoleSession>() {
+        session
+            .0
+            .push((String::from(line), output.clone(), SystemTime::now()));
+    }
+    // keep `console history` itself out, so clearing leaves the history empty
+    if !line.trim_start().starts_with("console history") {
+        if let Some(mut history) = world.get_resource_mut::<ConsoleHistory>() {
+            history.push(line);
+        }
+    }
+
+    output
+}
+
+/// Runs every `;` separated command on the line, heading each output with its command when
+/// there is more than one.
+fn run_line(line: &str, world: &mut World, check_lock: bool) -> String {
+    // comments echoed from scripts
+    if is_comment(line) {
+        return String::new();
+    }
+    let commands = split_commands(line);
+    let output = match commands[..] {
+        [] => return String::new(),
+        [command] => run_command(command, world, check_lock),
+        _ => commands
+            .into_iter()
+            .map(|command| {
+                let output = run_command(command, world, check_lock);
+                format!("--- {} ---\n{}\n", command, output)
+            })
+            .collect(),
+    };
+
+    with_response_prefix(world, &output)
+}
+
+fn with_response_prefix(world: &World, output: &str) -> String {
+    match world.get_resource::<ResponsePrefix>() {
+        Some(prefix) => prefix.apply(output),
+        None => String::from(output),
+    }
+}
+
+fn run_input_events(world: &mut World) {
+    let events: Vec<ConsoleInputEvent> = match world.get_resource_mut::<Events<ConsoleInputEvent>>()
+    {
+        Some(mut events) => events.drain().collect(),
+        None => return,
+    };
+
+    for event in events {
+        let bypass_lock = matches!(
+            world.get_resource::<LockBypassSources>(),
+            Some(bypass) if bypass.0.contains(&event.source)
+        );
+        let output = run_line(&event.command, world, !bypass_lock);
+        println!("\n[{}] {}", event.source, event.command);
+        println!("{}", output);
+        print!(">>> ");
+    }
+    io::stdout().flush().unwrap();
+}
+
+fn parse_input(world: &mut World) {
+    if world.resource::<EnteringConsole>().0 {
+        run_on_open_commands(world);
+    }
+    run_input_events(world);
+
+    match continue_list_operation(world) {
+        Some(ListProgress::Loading(percent)) => {
+            print!("\rLoading… ({}% complete)", percent);
             io::stdout().flush().unwrap();
             return;
         }
+        Some(ListProgress::Done(output)) => {
+            println!("\r{}", with_response_prefix(world, &output));
+            print!(">>> ");
+            io::stdout().flush().unwrap();
+        }
+        None => {}
+    }
 
-        let matches = matches_result.unwrap();
-
-        let output = match_commands(&matches, a, c, e, &mut pause, &*reflect);
+    if let Ok(line) = world.resource::<Receiver<String>>().try_recv() {
+        println!();
+        let output = run_line(&line, world, true);
 
         println!("{}", output);
         print!(">>> ");
@@ -42,6 +135,50 @@ fn parse_input(
     }
 }
 
+fn run_startup_commands(world: &mut World) {
+    let startup = world.resource::<StartupCommands>();
+    let mut lines = startup.commands.clone();
+    if let Some(path) = &startup.file {
+        match fs::read_to_string(path) {
+            Ok(file) => lines.extend(
+                file.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(String::from),
+            ),
+            // the file is only created once a command is added with `console run-on-startup`
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => println!(
+                "could not read startup commands from {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    echo_commands(lines, world);
+}
+
+fn run_on_open_commands(world: &mut World) {
+    let lines = world
+        .get_resource::<OnOpenCommands>()
+        .map(|on_open| on_open.0.clone())
+        .unwrap_or_default();
+    echo_commands(lines, world);
+}
+
+/// Runs `lines` as if they were typed at the prompt.
+fn echo_commands(lines: Vec<String>, world: &mut World) {
+    for line in lines {
+        // the prompt is already printed, echo the command after it as if it was typed
+        println!("{}", line);
+        let output = run_line(&line, world, true);
+        println!("{}", output);
+        print!(">>> ");
+    }
+    io::stdout().flush().unwrap();
+}
+
 fn spawn_io_thread(mut commands: Commands) {
     let thread_pool = AsyncComputeTaskPool::get();
     println!("Bevy Console Debugger.  Type 'help' for list of commands.");
@@ -61,13 +198,160 @@ fn spawn_io_thread(mut commands: Commands) {
     commands.insert_resource(rx);
 }
 
-pub struct ConsoleDebugPlugin;
+/// Runs the console systems right after [`CoreStage::Update`], which `world freeze` stops.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct ConsoleStage;
+
+#[derive(Default)]
+pub struct ConsoleDebugPlugin {
+    lock_hash: Option<String>,
+    startup_commands: Vec<String>,
+    startup_file: Option<PathBuf>,
+    on_open_commands: Vec<String>,
+    lock_bypass: Vec<CommandSource>,
+    inspectors: Vec<Inspector>,
+    scripts_dir: Option<PathBuf>,
+    response_prefix: String,
+    key_bindings: HashMap<KeyCode, String>,
+    history_file: Option<PathBuf>,
+}
+
+impl ConsoleDebugPlugin {
+    /// Start the console locked in release builds. `hash` is the bcrypt hash of the
+    /// password that `console unlock --password <pwd>` must be given.
+    pub fn with_lock_on_release_builds(mut self, hash: &str) -> Self {
+        self.lock_hash = Some(String::from(hash));
+        self
+    }
+
+    /// Let [`ConsoleInputEvent`]s from `source` run commands while the console is locked.
+    pub fn with_lock_bypass(mut self, source: CommandSource) -> Self {
+        self.lock_bypass.push(source);
+        self
+    }
+
+    /// Run `commands` once the app has started, as if they were typed into the console.
+    pub fn with_startup_commands(mut self, commands: Vec<&str>) -> Self {
+        self.startup_commands = commands.into_iter().map(String::from).collect();
+        self
+    }
+
+    /// Also run the commands in `path`, one per line, at startup. Lines starting with `#`
+    /// are skipped and `console run-on-startup <command>` appends to the file.
+    pub fn with_startup_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.startup_file = Some(path.into());
+        self
+    }
+
+    /// Run `commands` every time the console is opened by pausing the game, before it
+    /// accepts input. `console autoexec --on-open <command>` adds to them at runtime.
+    pub fn with_on_open_commands(mut self, commands: Vec<&str>) -> Self {
+        self.on_open_commands = commands.into_iter().map(String::from).collect();
+        self
+    }
+
+    /// Let `scripts list` and `scripts run <name>` find the `.console` and `.txt` files in
+    /// `path`. `scripts reload` picks up files added after startup.
+    pub fn with_scripts_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.scripts_dir = Some(path.into());
+        self
+    }
+
+    /// Put `prefix` in front of every line of command output, i.e. `"| "`, to set it apart
+    /// from log lines when the console shares a scrollback with them.
+    pub fn with_response_prefix(mut self, prefix: &str) -> Self {
+        self.response_prefix = String::from(prefix);
+        self
+    }
+
+    /// Keep the commands run in the console in `path`, one per line. It is read at startup and
+    /// written on exit, `console history save` and `console history load` do it on demand.
+    pub fn with_history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// Bind F5 to `counts`, F6 to `world summary` and F7 to `world validate`. `console bind-key`
+    /// changes them at runtime.
+    pub fn with_default_key_bindings(mut self) -> Self {
+        self.key_bindings.extend([
+            (KeyCode::F5, String::from("counts")),
+            (KeyCode::F6, String::from("world summary")),
+            (KeyCode::F7, String::from("world validate")),
+        ]);
+        self
+    }
+
+    /// Add a check to `world inspect`, next to the built-in ones.
+    pub fn register_inspector(mut self, inspector: Inspector) -> Self {
+        self.inspectors.push(inspector);
+        self
+    }
+}
+
 impl Plugin for ConsoleDebugPlugin {
     fn build(&self, app: &mut App) {
+        let lock = if cfg!(debug_assertions) {
+            None
+        } else {
+            self.lock_hash.clone()
+        };
+
         app.insert_resource(Pause(false))
             .insert_resource(EnteringConsole(false))
+            .insert_resource(ConsoleLock(lock))
+            .init_resource::<FpsHudVisible>()
+            .init_resource::<FpsHudHistory>()
+            .init_resource::<FrameCount>()
+            .init_resource::<ConsoleEvents>()
+            .init_resource::<ConsoleSession>()
+            .init_resource::<WorldFrozen>()
+            .init_resource::<StepMode>()
+            .init_resource::<WatchCounts>()
+            .insert_resource(Inspectors(self.inspectors.clone()))
+            .insert_resource(ScriptsDir::new(self.scripts_dir.clone()))
+            .insert_resource(ResponsePrefix(self.response_prefix.clone()))
+            .insert_resource(KeyBindings(self.key_bindings.clone()))
+            .insert_resource(ConsoleHistory::new(self.history_file.clone()))
+            .insert_resource(OnOpenCommands(self.on_open_commands.clone()))
+            .insert_resource(LockBypassSources(self.lock_bypass.clone()))
+            .add_event::<ConsoleInputEvent>()
+            .insert_resource(StartupCommands {
+                commands: self.startup_commands.clone(),
+                file: self.startup_file.clone(),
+            })
             .add_startup_system(spawn_io_thread)
-            .add_system(parse_input.with_run_criteria(pause))
-            .add_system(input_pause);
+            .add_startup_system_to_stage(
+                StartupStage::PostStartup,
+                run_startup_commands.exclusive_system(),
+            )
+            .add_system_to_stage(CoreStage::First, count_frames)
+            .add_system_to_stage(CoreStage::First, take_step)
+            .stage(CoreStage::Update, |stage: &mut SystemStage| {
+                stage.set_run_criteria(not_frozen)
+            })
+            .add_stage_after(CoreStage::Update, ConsoleStage, SystemStage::parallel())
+            .add_system_to_stage(
+                ConsoleStage,
+                parse_input.exclusive_system().with_run_criteria(pause),
+            )
+            .add_system_to_stage(ConsoleStage, input_pause)
+            .add_system_to_stage(ConsoleStage, run_key_bindings)
+            .add_system_to_stage(ConsoleStage, print_fps_hud)
+            .add_system_to_stage(CoreStage::Last, save_history_on_exit)
+            .add_system_to_stage(CoreStage::Last, print_watched_counts.exclusive_system());
+
+        if cfg!(debug_assertions) {
+            app.init_resource::<TickToTimeMap>()
+                .add_system_to_stage(CoreStage::First, record_tick_time.exclusive_system());
+        }
+
+        #[cfg(feature = "console-puffin")]
+        app.init_resource::<ProfileScopes>()
+            .init_resource::<ProfileFrames>()
+            .add_system_to_stage(CoreStage::First, profile::new_frame);
+
+        #[cfg(feature = "console-render")]
+        render::setup(app);
     }
 }
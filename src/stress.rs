@@ -0,0 +1,224 @@
+use std::{
+    fmt::Write,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    ecs::{entity::Entity, reflect::ReflectComponent, world::World},
+    reflect::{std_traits::ReflectDefault, TypeRegistry},
+};
+use clap::{arg, App, AppSettings, ArgMatches};
+
+use crate::ecs::entity_label;
+
+/// Entities spawned by `stress entities`, the only ones `stress despawn` removes.
+#[derive(Default)]
+pub struct StressEntities(pub Vec<Entity>);
+
+pub fn build_commands(app: App) -> App {
+    let app = app.subcommand(
+        App::new("stress")
+            .about("spawn and despawn entities to see how the app copes with many of them")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                App::new("entities")
+                    .about("spawn entities with default components")
+                    .args([
+                        arg!(--count <N> "number of entities to spawn"),
+                        arg!(--components <Components> "comma separated components to add, they need #[reflect(Component, Default)]")
+                            .required(false),
+                        arg!(--"dry-run" "only print what would be spawned"),
+                    ]),
+            )
+            .subcommand(
+                App::new("despawn")
+                    .about("despawn random entities spawned by stress entities")
+                    .args([
+                        arg!(--count <N> "number of entities to despawn"),
+                        arg!(--seed <N> "seed for picking the entities, random when not set")
+                            .required(false),
+                        arg!(--"dry-run" "only print the entities that would be despawned, run again with the printed --seed to despawn them"),
+                    ]),
+            ),
+    );
+
+    app
+}
+
+pub fn match_commands(matches: &ArgMatches, world: &mut World) -> String {
+    match matches.subcommand() {
+        Some(("stress", matches)) => match matches.subcommand() {
+            Some(("entities", matches)) => match matches.value_of_t("count") {
+                Ok(count) => {
+                    let components: Vec<&str> = matches
+                        .value_of("components")
+                        .map(|names| names.split(',').filter(|name| !name.is_empty()).collect())
+                        .unwrap_or_default();
+                    spawn_entities(world, count, &components, matches.is_present("dry-run"))
+                }
+                Err(e) => format!("--count: {}\n", e),
+            },
+            Some(("despawn", matches)) => match matches.value_of_t("count") {
+                Ok(count) => {
+                    let seed = matches.value_of_t("seed").unwrap_or_else(|_| random_seed());
+                    despawn_entities(world, count, seed, matches.is_present("dry-run"))
+                }
+                Err(e) => format!("--count: {}\n", e),
+            },
+            _ => String::from("this line should not be hittable"),
+        },
+        _ => String::from(""),
+    }
+}
+
+/// Looks up the reflection data needed to add a default `name` component to an entity.
+fn find_component(
+    world: &World,
+    name: &str,
+) -> Result<(ReflectComponent, ReflectDefault), String> {
+    let registry = world
+        .get_resource::<TypeRegistry>()
+        .ok_or("no TypeRegistry resource found")?
+        .read();
+    let registration = registry
+        .iter()
+        .find(|registration| registration.type_name() == name || registration.short_name() == name)
+        .ok_or_else(|| format!("no type registered with name {}", name))?;
+
+    let reflect_component = registration
+        .data::<ReflectComponent>()
+        .ok_or_else(|| format!("{} is missing #[reflect(Component)]", name))?;
+    let reflect_default = registration
+        .data::<ReflectDefault>()
+        .ok_or_else(|| format!("{} is missing #[reflect(Default)]", name))?;
+
+    Ok((reflect_component.clone(), reflect_default.clone()))
+}
+
+fn spawn_entities(world: &mut World, count: usize, names: &[&str], dry_run: bool) -> String {
+    let components: Vec<(ReflectComponent, ReflectDefault)> =
+        match names.iter().map(|name| find_component(world, name)).collect() {
+            Ok(components) => components,
+            Err(e) => return format!("no entities spawned, {}\n", e),
+        };
+    if dry_run && names.is_empty() {
+        return format!("would spawn {} entities without components.\n", count);
+    }
+    if dry_run {
+        return format!(
+            "would spawn {} entities with {} components: {}\n",
+            count,
+            names.len(),
+            names.join(", ")
+        );
+    }
+
+    // the components are only known through reflection, so they can't be queued as
+    // commands, but the console already has the whole world to itself
+    let start = Instant::now();
+    let mut spawned = Vec::with_capacity(count);
+    for _ in 0..count {
+        let entity = world.spawn().id();
+        for (reflect_component, reflect_default) in &components {
+            reflect_component.insert(world, entity, &*reflect_default.default());
+        }
+        spawned.push(entity);
+    }
+    let elapsed = start.elapsed();
+
+    world
+        .get_resource_or_insert_with(StressEntities::default)
+        .0
+        .extend(spawned);
+
+    format!(
+        "spawned {} entities in {:.2} ms.\n",
+        count,
+        elapsed.as_secs_f64() * 1000.0
+    )
+}
+
+fn despawn_entities(world: &mut World, count: usize, seed: u64, dry_run: bool) -> String {
+    let mut entities = world
+        .get_resource::<StressEntities>()
+        .map(|stress| stress.0.clone())
+        .unwrap_or_default();
+    // the app may have despawned some of them itself
+    entities.retain(|entity| world.get_entity(*entity).is_some());
+    let count = count.min(entities.len());
+
+    shuffle_front(&mut entities, count, seed);
+
+    if dry_run {
+        let mut output = String::new();
+        entities[..count].iter().for_each(|entity| {
+            writeln!(output, "would despawn {}", describe_entity(world, *entity)).unwrap()
+        });
+        writeln!(
+            output,
+            "{} entities with seed {}, run with --seed {} to despawn them.",
+            count, seed, seed
+        )
+        .unwrap();
+        return output;
+    }
+
+    let start = Instant::now();
+    entities.drain(..count).for_each(|entity| {
+        world.despawn(entity);
+    });
+    let elapsed = start.elapsed();
+
+    let left = entities.len();
+    world.insert_resource(StressEntities(entities));
+
+    format!(
+        "despawned {} entities in {:.2} ms with seed {}, {} stress entities left.\n",
+        count,
+        elapsed.as_secs_f64() * 1000.0,
+        seed,
+        left
+    )
+}
+
+/// `entity 42v0 (archetype 3, 5 components)`
+fn describe_entity(world: &World, entity: Entity) -> String {
+    let archetype = world
+        .entities()
+        .get(entity)
+        .and_then(|location| world.archetypes().get(location.archetype_id));
+    match archetype {
+        Some(archetype) => format!(
+            "entity {} (archetype {}, {} components)",
+            entity_label(&entity),
+            archetype.id().index(),
+            archetype.components().count()
+        ),
+        None => format!("entity {}", entity_label(&entity)),
+    }
+}
+
+pub(crate) fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// Moves `count` randomly picked items to the front, the same ones for the same `seed`.
+pub(crate) fn shuffle_front<T>(items: &mut [T], count: usize, seed: u64) {
+    let mut state = seed;
+    for i in 0..count.min(items.len()) {
+        let j = i + (next_random(&mut state) % (items.len() - i) as u64) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// splitmix64, good enough to shuffle entities without pulling in `rand`
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
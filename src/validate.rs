@@ -0,0 +1,133 @@
+use std::fmt::Write;
+
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    hierarchy::{Children, Parent},
+};
+
+use crate::ecs::entity_label;
+
+/// A consistency check run by `world validate`, returning one message per violation.
+type Validator = fn(&World) -> Vec<String>;
+
+const VALIDATORS: [Validator; 3] = [children_link_back, parents_list_child, own_parent];
+
+pub fn validate_world(world: &World) -> String {
+    let errors: Vec<String> = VALIDATORS
+        .iter()
+        .flat_map(|validator| validator(world))
+        .collect();
+
+    let mut output = String::new();
+    if errors.is_empty() {
+        writeln!(output, "no violations found by {} checks.", VALIDATORS.len()).unwrap();
+        return output;
+    }
+
+    errors
+        .iter()
+        .for_each(|error| writeln!(output, "{}", error).unwrap());
+    writeln!(
+        output,
+        "{} violations found by {} checks.",
+        errors.len(),
+        VALIDATORS.len()
+    )
+    .unwrap();
+
+    output
+}
+
+fn all_entities(world: &World) -> impl Iterator<Item = Entity> + '_ {
+    world
+        .archetypes()
+        .iter()
+        .flat_map(|archetype| archetype.entities())
+        .copied()
+}
+
+/// Every child in `Children` exists and has a `Parent` pointing back.
+fn children_link_back(world: &World) -> Vec<String> {
+    let mut errors = Vec::new();
+    for entity in all_entities(world) {
+        let children = match world.get::<Children>(entity) {
+            Some(children) => children,
+            None => continue,
+        };
+        for child in children.iter() {
+            if world.get_entity(*child).is_none() {
+                errors.push(format!(
+                    "entity {} lists child {} which doesn't exist, fix: remove it from Children or despawn with despawn_recursive",
+                    entity_label(&entity),
+                    entity_label(child)
+                ));
+                continue;
+            }
+            match world.get::<Parent>(*child) {
+                Some(parent) if **parent == entity => {}
+                Some(parent) => errors.push(format!(
+                    "entity {} lists child {} whose Parent is {}, fix: move the child with push_children instead of setting Parent",
+                    entity_label(&entity),
+                    entity_label(child),
+                    entity_label(parent)
+                )),
+                None => errors.push(format!(
+                    "entity {} lists child {} which has no Parent, fix: add children with push_children or add_child",
+                    entity_label(&entity),
+                    entity_label(child)
+                )),
+            }
+        }
+    }
+
+    errors
+}
+
+/// Every `Parent` points to an existing entity whose `Children` lists the child.
+fn parents_list_child(world: &World) -> Vec<String> {
+    let mut errors = Vec::new();
+    for entity in all_entities(world) {
+        let parent = match world.get::<Parent>(entity) {
+            Some(parent) => **parent,
+            None => continue,
+        };
+        if parent == entity {
+            // reported by own_parent
+            continue;
+        }
+        if world.get_entity(parent).is_none() {
+            errors.push(format!(
+                "entity {} has Parent {} which doesn't exist, fix: despawn the parent with despawn_recursive, or remove Parent",
+                entity_label(&entity),
+                entity_label(&parent)
+            ));
+            continue;
+        }
+        let listed = matches!(
+            world.get::<Children>(parent),
+            Some(children) if children.contains(&entity)
+        );
+        if !listed {
+            errors.push(format!(
+                "entity {} has Parent {} which doesn't list it in Children, fix: set the parent with push_children or add_child",
+                entity_label(&entity),
+                entity_label(&parent)
+            ));
+        }
+    }
+
+    errors
+}
+
+/// No entity is its own parent.
+fn own_parent(world: &World) -> Vec<String> {
+    all_entities(world)
+        .filter(|entity| matches!(world.get::<Parent>(*entity), Some(parent) if **parent == *entity))
+        .map(|entity| {
+            format!(
+                "entity {} is its own parent, fix: remove its Parent component",
+                entity_label(&entity)
+            )
+        })
+        .collect()
+}